@@ -0,0 +1,273 @@
+use crate::geo_attributes::GeoAttributes;
+use crate::geo_param_error::GeoParamError;
+use crate::map_sources::MapSources;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The URL parameters for the `/api/v1/coordinates` endpoint.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ApiQuery {
+    params: Option<String>,
+    language: Option<String>,
+    pagename: Option<String>,
+    title: Option<String>,
+}
+
+impl ApiQuery {
+    /// Create a new ApiQuery for testing with params
+    #[cfg(test)]
+    pub fn new_for_test(params: &str) -> Self {
+        Self {
+            params: Some(params.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The parsed attributes of the `params`
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ApiAttributes {
+    #[serde(rename = "type")]
+    typename: Option<String>,
+    region: Option<String>,
+    country: Option<String>,
+    subdivision: Option<String>,
+    globe: Option<String>,
+    scale: Option<f64>,
+    /// Diameter in metres
+    dim: Option<f64>,
+    heading: Option<f64>,
+    /// Elevation in metres
+    elevation: Option<f64>,
+    source: Option<String>,
+}
+
+impl ApiAttributes {
+    fn new(attr: &GeoAttributes) -> Self {
+        let region = attr.region();
+        Self {
+            typename: attr.location_type().map(|t| t.as_str().to_string()),
+            region: region.map(|r| r.raw().to_string()),
+            country: region.map(|r| r.country().to_string()),
+            subdivision: region.and_then(|r| r.subdivision()).map(str::to_string),
+            globe: attr
+                .get("globe")
+                .filter(|g| !g.is_empty())
+                .map(str::to_string),
+            scale: attr.scale(),
+            dim: attr.dim(),
+            heading: attr.heading(),
+            elevation: attr.elevation(),
+            source: attr.source().map(str::to_string),
+        }
+    }
+}
+
+/// All values computed from `params`, as used for the `{placeholders}` in the GeoTemplate
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ApiCoordinates {
+    params: String,
+    latitude: f64,
+    longitude: f64,
+    attributes: ApiAttributes,
+    values: BTreeMap<String, String>,
+}
+
+impl ApiCoordinates {
+    pub fn new(query: &ApiQuery) -> Result<Self, ApiError> {
+        let params = query
+            .params
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| {
+                ApiError::new(
+                    "missing_params",
+                    "No parameters given (&params= is empty or missing)",
+                )
+            })?;
+        let language = query.language.as_deref().unwrap_or("en");
+        let pagename = query.pagename.as_deref().unwrap_or_default();
+        let title = query
+            .title
+            .clone()
+            .unwrap_or_else(|| pagename.replace('_', " "));
+
+        let map_sources =
+            MapSources::new(params, language).map_err(|e| ApiError::invalid_params(&e))?;
+        let latitude = map_sources.p().latdeg();
+        let longitude = map_sources.p().londeg();
+        let attr = map_sources.get_attr();
+        let attributes = ApiAttributes::new(&attr);
+        let values = map_sources
+            .build_rep_map(pagename, &title, attr)
+            .into_iter()
+            .collect();
+
+        Ok(Self {
+            params: params.to_string(),
+            latitude,
+            longitude,
+            attributes,
+            values,
+        })
+    }
+
+    pub const fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub const fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    pub const fn attributes(&self) -> &ApiAttributes {
+        &self.attributes
+    }
+
+    pub const fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiErrorDetails {
+    code: String,
+    message: String,
+}
+
+/// Error object returned by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    status: StatusCode,
+    error: ApiErrorDetails,
+}
+
+impl ApiError {
+    /// Create a new error for a bad request
+    pub fn new(code: &str, message: &str) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            error: ApiErrorDetails {
+                code: code.to_string(),
+                message: message.to_string(),
+            },
+        }
+    }
+
+    /// Create an error for `params` that could not be parsed, with the code of the
+    /// `GeoParamError` if there is one
+    pub fn invalid_params(err: &anyhow::Error) -> Self {
+        let code = err
+            .downcast_ref::<GeoParamError>()
+            .map_or("invalid_params", GeoParamError::code);
+        Self::new(code, &err.to_string())
+    }
+
+    pub const fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub const fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn code(&self) -> &str {
+        &self.error.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.error.message
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_coordinates() {
+        let query = ApiQuery::new_for_test("51_30_28_N_0_07_41_W_type:city_region:GB-LND");
+        let coords = ApiCoordinates::new(&query).unwrap();
+        assert!((coords.latitude() - 51.507778).abs() < 1e-6);
+        assert!((coords.longitude() + 0.128056).abs() < 1e-6);
+        assert_eq!(coords.attributes().typename, Some("city".to_string()));
+        assert_eq!(coords.attributes().region, Some("GB-LND".to_string()));
+        assert_eq!(coords.attributes().country, Some("GB".to_string()));
+        assert_eq!(coords.attributes().subdivision, Some("LND".to_string()));
+        assert_eq!(coords.attributes().globe, None);
+        assert_eq!(coords.attributes().scale, Some(100_000.0));
+        assert_eq!(coords.values().get("latNS").unwrap(), "N");
+        assert_eq!(coords.values().get("utmzone").unwrap(), "30U");
+        assert!(coords.values().get("osgb36ref").unwrap().starts_with("TQ"));
+        assert_eq!(coords.values().get("osmzoom").unwrap(), "12");
+        assert!(coords.values().contains_key("ch1903easting"));
+        assert!(coords.values().contains_key("span"));
+    }
+
+    #[test]
+    fn test_api_coordinates_json() {
+        let query = ApiQuery::new_for_test("10.70_S_335.25_E_globe:Venus");
+        let coords = ApiCoordinates::new(&query).unwrap();
+        let json = serde_json::to_value(&coords).unwrap();
+        assert_eq!(json["attributes"]["type"], serde_json::Value::Null);
+        assert_eq!(json["attributes"]["globe"], "Venus");
+        assert_eq!(json["latitude"], -10.7);
+        assert_eq!(json["values"]["latdegdec"], "-10.7");
+    }
+
+    #[test]
+    fn test_api_coordinates_raw_values() {
+        // The JSON carries the values as given, they are only escaped for the HTML page
+        let query = ApiQuery {
+            params: Some("48.8584_N_2.2945_E_source:a&b<c".to_string()),
+            pagename: Some("AT&T_<Tower>".to_string()),
+            ..Default::default()
+        };
+        let coords = ApiCoordinates::new(&query).unwrap();
+        assert_eq!(coords.values()["params"], "48.8584_N_2.2945_E_source:a&b<c");
+        assert_eq!(coords.values()["pagename"], "AT&T_<Tower>");
+        assert_eq!(coords.values()["title"], "AT&T <Tower>");
+        assert_eq!(coords.values()["language"], "en");
+    }
+
+    #[test]
+    fn test_api_missing_params() {
+        let err = ApiCoordinates::new(&ApiQuery::default()).unwrap_err();
+        assert_eq!(err.code(), "missing_params");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_api_invalid_params() {
+        let err =
+            ApiCoordinates::new(&ApiQuery::new_for_test("invalid coordinates here")).unwrap_err();
+        assert_eq!(err.code(), "unrecognized_format");
+        assert_eq!(
+            err.message(),
+            "Unrecognized format at token 1 (\"invalid\")"
+        );
+
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["error"]["code"], "unrecognized_format");
+
+        let range = ApiCoordinates::new(&ApiQuery::new_for_test("95_N_10_E")).unwrap_err();
+        assert_eq!(range.code(), "latitude_out_of_range");
+        assert_eq!(range.status(), StatusCode::BAD_REQUEST);
+
+        let other = ApiError::invalid_params(&anyhow::anyhow!("Something else"));
+        assert_eq!(other.code(), "invalid_params");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::geo_param::{GeoParam, METRES_PER_DEGREE};
+use crate::insert_map;
+use std::collections::HashMap;
+
+/// The extent of a coordinate range (e.g., "40 N 74 W to 41 N 73 W"),
+/// or a single point if there is no range
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BoundingBox {
+    latmin: f64,
+    latmax: f64,
+    lonmin: f64, /* western edge, larger than lonmax across the antimeridian */
+    lonmax: f64,
+    latcenter: f64,
+    loncenter: f64,
+    lon_span: f64,
+    is_range: bool,
+}
+
+impl BoundingBox {
+    pub fn new(p: &GeoParam) -> Self {
+        Self {
+            latmin: p.latdeg_min(),
+            latmax: p.latdeg_max(),
+            lonmin: p.londeg_min(),
+            lonmax: p.londeg_max(),
+            latcenter: p.latdeg(),
+            loncenter: p.londeg(),
+            lon_span: p.lon_span(),
+            is_range: p.is_range(),
+        }
+    }
+
+    pub const fn is_range(&self) -> bool {
+        self.is_range
+    }
+
+    /// The larger of width and height of the box, in metres
+    pub fn extent_m(&self) -> f64 {
+        let height = (self.latmax - self.latmin) * METRES_PER_DEGREE;
+        let width = self.lon_span * self.latcenter.to_radians().cos() * METRES_PER_DEGREE;
+        height.max(width)
+    }
+
+    pub fn add_rep_map(&self, rep_map: &mut HashMap<String, String>) {
+        insert_map!(rep_map, {
+            "latmin" => self.latmin,
+            "latmax" => self.latmax,
+            "lonmin" => self.lonmin,
+            "lonmax" => self.lonmax,
+            "latcenter" => self.latcenter,
+            "loncenter" => self.loncenter,
+            // West, south, east, north, as used by OpenStreetMap and GeoJSON
+            "bbox" => format!("{},{},{},{}", self.lonmin, self.latmin, self.lonmax, self.latmax),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_box_range() {
+        let geo = GeoParam::new("40_N_74_W_to_41_N_73_W").unwrap();
+        let bbox = BoundingBox::new(&geo);
+        assert!(bbox.is_range());
+
+        let mut rep_map = HashMap::new();
+        bbox.add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("latmin").unwrap(), "40");
+        assert_eq!(rep_map.get("latmax").unwrap(), "41");
+        assert_eq!(rep_map.get("lonmin").unwrap(), "-74");
+        assert_eq!(rep_map.get("lonmax").unwrap(), "-73");
+        assert_eq!(rep_map.get("latcenter").unwrap(), "40.5");
+        assert_eq!(rep_map.get("loncenter").unwrap(), "-73.5");
+        assert_eq!(rep_map.get("bbox").unwrap(), "-74,40,-73,41");
+
+        // One degree of latitude is longer than one degree of longitude at 40.5 N
+        assert!((bbox.extent_m() - METRES_PER_DEGREE).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bounding_box_antimeridian() {
+        let geo = GeoParam::new("20_S_170_E_to_10_S_170_W").unwrap();
+        let bbox = BoundingBox::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        bbox.add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("bbox").unwrap(), "170,-20,-170,-10");
+        assert_eq!(rep_map.get("loncenter").unwrap(), "180");
+
+        // 20 degrees of longitude, not 340
+        let expected = 20.0 * 15_f64.to_radians().cos() * METRES_PER_DEGREE;
+        assert!((bbox.extent_m() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bounding_box_point() {
+        let geo = GeoParam::new("51_30_28_N_0_07_41_W").unwrap();
+        let bbox = BoundingBox::new(&geo);
+        assert!(!bbox.is_range());
+        assert_eq!(bbox.extent_m(), 0.0);
+        assert_eq!(bbox.latmin, bbox.latmax);
+        assert_eq!(bbox.lonmin, geo.londeg());
+    }
+}
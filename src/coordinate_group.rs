@@ -14,6 +14,9 @@ pub struct CoordinateGroup {
     latdeg_outer_abs: i32,
     londeg_outer_abs: i32,
     longantipodes: f64,
+    precision_m: f64,
+    londeg_east_360: f64,
+    londeg_west_360: f64,
 }
 
 impl CoordinateGroup {
@@ -65,6 +68,9 @@ impl CoordinateGroup {
             latdeg_outer_abs,
             londeg_outer_abs,
             longantipodes,
+            precision_m: (p.precision_m() * 10.0).round() / 10.0,
+            londeg_east_360: p.londeg_east_360(),
+            londeg_west_360: p.londeg_west_360(),
         }
     }
 
@@ -97,6 +103,9 @@ impl CoordinateGroup {
             "lonsecint" => self.lon.sec() as i32,
             "latNS" => self.lat.ns(),
             "lonEW" => self.lon.ew(),
+            "precision_m" => self.precision_m,
+            "londeg360e" => self.londeg_east_360,
+            "londeg360w" => self.londeg_west_360,
         });
     }
 }
@@ -162,6 +171,30 @@ mod tests {
         assert_eq!(rep_map.get("lonEW").unwrap(), "W");
     }
 
+    #[test]
+    fn test_coordinate_group_precision() {
+        let geo = GeoParam::new("51_30_28_N_0_07_41_W").unwrap();
+        let cg = CoordinateGroup::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        cg.add_rep_map(&mut rep_map);
+
+        // One second of latitude
+        assert_eq!(rep_map.get("precision_m").unwrap(), "30.9");
+    }
+
+    #[test]
+    fn test_coordinate_group_longitude_360() {
+        let geo = GeoParam::new("13_19_N_169_9_W_globe:Moon").unwrap();
+        let cg = CoordinateGroup::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        cg.add_rep_map(&mut rep_map);
+
+        assert_eq!(rep_map.get("londeg360e").unwrap(), "190.85");
+        assert_eq!(rep_map.get("londeg360w").unwrap(), "169.15");
+    }
+
     #[test]
     fn test_coordinate_group_outer_abs() {
         let geo = GeoParam::new("40.3_N_74.7_W").unwrap();
@@ -0,0 +1,271 @@
+/**
+ *  Reference ellipsoids, geodetic datums and seven-parameter Helmert datum transformations
+ *
+ *  See also:
+ *  https://www.ordnancesurvey.co.uk/documents/resources/guide-coordinate-systems-great-britain.pdf
+ *  https://www.osi.ie/wp-content/uploads/2015/05/transformations_booklet.pdf
+ *  https://www.swisstopo.admin.ch/en/knowledge-facts/surveying-geodesy/reference-frames.html
+ */
+use std::f64::consts::PI;
+
+/// A reference ellipsoid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipsoid {
+    radius: f64,       /* major semi axis = a */
+    eccentricity: f64, /* square of eccentricity */
+}
+
+impl Ellipsoid {
+    /// WGS-84, as used by GPS and by the coordinates in `params`
+    pub const WGS84: Ellipsoid = Ellipsoid::new(6378137.0, 0.00669437999014);
+    /// Airy 1830, as used by OSGB36
+    pub const AIRY_1830: Ellipsoid = Ellipsoid::new(6377563.396, 0.0066705397616);
+    /// Airy Modified 1849, as used by the Irish Grid (TM75)
+    pub const AIRY_MODIFIED: Ellipsoid = Ellipsoid::new(6377340.189, 0.00667054015);
+    /// Bessel 1841, as used by CH1903, CH1903+, Amersfoort, DHDN and MGI
+    pub const BESSEL_1841: Ellipsoid = Ellipsoid::new(6377397.155, 0.006674372230614);
+    /// International 1924 (Hayford), as used by BD72
+    pub const INTERNATIONAL_1924: Ellipsoid = Ellipsoid::new(6378388.0, 0.00672267002233);
+    /// GRS 1980, as used by ETRS89, RGF93 and NZGD2000
+    pub const GRS80: Ellipsoid = Ellipsoid::new(6378137.0, 0.00669438002290);
+
+    pub const fn new(radius: f64, eccentricity: f64) -> Self {
+        Self {
+            radius,
+            eccentricity,
+        }
+    }
+
+    pub const fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub const fn eccentricity(&self) -> f64 {
+        self.eccentricity
+    }
+
+    /// Convert latitude, longitude in decimal degrees and ellipsoidal height in metres
+    /// to geocentric cartesian coordinates
+    pub fn lat_lon_to_cartesian(&self, latitude: f64, longitude: f64, height: f64) -> [f64; 3] {
+        let lat_rad = latitude * PI / 180.0;
+        let lon_rad = longitude * PI / 180.0;
+        let v = self.radius / (1.0 - self.eccentricity * lat_rad.sin().powi(2)).sqrt();
+        [
+            (v + height) * lat_rad.cos() * lon_rad.cos(),
+            (v + height) * lat_rad.cos() * lon_rad.sin(),
+            (v * (1.0 - self.eccentricity) + height) * lat_rad.sin(),
+        ]
+    }
+
+    /// Convert geocentric cartesian coordinates to latitude, longitude in decimal degrees
+    pub fn cartesian_to_lat_lon(&self, xyz: [f64; 3]) -> (f64, f64) {
+        let [x, y, z] = xyz;
+        let p = x.hypot(y);
+        let mut lat_rad = z.atan2(p * (1.0 - self.eccentricity));
+        /* converges to sub-millimetre after a few iterations */
+        for _ in 0..10 {
+            let v = self.radius / (1.0 - self.eccentricity * lat_rad.sin().powi(2)).sqrt();
+            lat_rad = (z + self.eccentricity * v * lat_rad.sin()).atan2(p);
+        }
+        (lat_rad * 180.0 / PI, y.atan2(x) * 180.0 / PI)
+    }
+}
+
+/// Seven-parameter Helmert transformation, position vector convention
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Helmert {
+    tx: f64, /* translation, metres */
+    ty: f64,
+    tz: f64,
+    rx: f64, /* rotation, arc seconds */
+    ry: f64,
+    rz: f64,
+    s: f64, /* scale, ppm */
+}
+
+impl Helmert {
+    /// WGS-84 to OSGB36, accurate to a few metres
+    pub const WGS84_TO_OSGB36: Helmert = Helmert::new(
+        [-446.448, 125.157, -542.060],
+        [-0.1502, -0.2470, -0.8421],
+        20.4894,
+    );
+    /// WGS-84 to the Irish Grid datum (TM75), accurate to about a metre
+    pub const WGS84_TO_TM75: Helmert =
+        Helmert::new([-482.530, 130.596, -564.557], [1.042, 0.214, 0.631], -8.150);
+    /// WGS-84 to the Swiss CH1903 datum, the reverse of EPSG:1753, accurate to about a metre
+    pub const WGS84_TO_CH1903: Helmert =
+        Helmert::new([-674.4, -15.1, -405.3], [0.0, 0.0, 0.0], 0.0);
+    /// WGS-84 to the Swiss CH1903+ datum, the reverse of EPSG:1676, accurate to about a metre
+    pub const WGS84_TO_CH1903PLUS: Helmert =
+        Helmert::new([-674.374, -15.056, -405.346], [0.0, 0.0, 0.0], 0.0);
+
+    /// WGS-84 to the Belgian BD72 datum, the reverse of EPSG:15929, accurate to about a metre
+    pub const WGS84_TO_BD72: Helmert = Helmert::new(
+        [106.8686, -52.2978, 103.7239],
+        [-0.3366, 0.457, -1.8422],
+        1.2747,
+    );
+
+    /// WGS-84 to the Dutch Amersfoort datum, the reverse of EPSG:1672, accurate to about a metre
+    pub const WGS84_TO_AMERSFOORT: Helmert = Helmert::new(
+        [-565.417, -50.3319, -465.552],
+        [0.398957, -0.343988, 1.8774],
+        -4.0725,
+    );
+    /// WGS-84 to the German DHDN datum, the reverse of EPSG:1777, accurate to a few metres
+    pub const WGS84_TO_DHDN: Helmert =
+        Helmert::new([-598.1, -73.7, -418.2], [-0.202, -0.045, 2.455], -6.7);
+    /// WGS-84 to the Austrian MGI datum, the reverse of EPSG:1618, accurate to a few metres
+    pub const WGS84_TO_MGI: Helmert = Helmert::new(
+        [-577.326, -90.129, -463.919],
+        [-5.137, -1.474, -5.297],
+        -2.4232,
+    );
+
+    pub const fn new(translation: [f64; 3], rotation: [f64; 3], scale: f64) -> Self {
+        Self {
+            tx: translation[0],
+            ty: translation[1],
+            tz: translation[2],
+            rx: rotation[0],
+            ry: rotation[1],
+            rz: rotation[2],
+            s: scale,
+        }
+    }
+
+    /// The approximate reverse transformation
+    pub const fn inverse(&self) -> Self {
+        Self::new(
+            [-self.tx, -self.ty, -self.tz],
+            [-self.rx, -self.ry, -self.rz],
+            -self.s,
+        )
+    }
+
+    /// Transform geocentric cartesian coordinates
+    pub fn transform_cartesian(&self, xyz: [f64; 3]) -> [f64; 3] {
+        let [x, y, z] = xyz;
+        let arcsec = PI / (180.0 * 3600.0);
+        let (rx, ry, rz) = (self.rx * arcsec, self.ry * arcsec, self.rz * arcsec);
+        let s = 1.0 + self.s * 1e-6;
+        [
+            self.tx + s * (x - rz * y + ry * z),
+            self.ty + s * (rz * x + y - rx * z),
+            self.tz + s * (-ry * x + rx * y + z),
+        ]
+    }
+
+    /// Transform latitude, longitude in decimal degrees from one datum to another
+    pub fn transform_lat_lon(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        from: &Ellipsoid,
+        to: &Ellipsoid,
+    ) -> (f64, f64) {
+        let xyz = from.lat_lon_to_cartesian(latitude, longitude, 0.0);
+        to.cartesian_to_lat_lon(self.transform_cartesian(xyz))
+    }
+}
+
+/// A geodetic datum, as its ellipsoid and the transformation from WGS-84
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Datum {
+    ellipsoid: Ellipsoid,
+    shift: Option<Helmert>, /* None if within a metre of WGS-84 */
+}
+
+impl Datum {
+    pub const ETRS89: Datum = Datum::new(Ellipsoid::GRS80, None);
+    pub const NZGD2000: Datum = Datum::new(Ellipsoid::GRS80, None);
+    pub const RGF93: Datum = Datum::new(Ellipsoid::GRS80, None);
+    pub const AMERSFOORT: Datum =
+        Datum::new(Ellipsoid::BESSEL_1841, Some(Helmert::WGS84_TO_AMERSFOORT));
+    pub const DHDN: Datum = Datum::new(Ellipsoid::BESSEL_1841, Some(Helmert::WGS84_TO_DHDN));
+    pub const MGI: Datum = Datum::new(Ellipsoid::BESSEL_1841, Some(Helmert::WGS84_TO_MGI));
+    pub const BD72: Datum = Datum::new(Ellipsoid::INTERNATIONAL_1924, Some(Helmert::WGS84_TO_BD72));
+    pub const OSGB36: Datum = Datum::new(Ellipsoid::AIRY_1830, Some(Helmert::WGS84_TO_OSGB36));
+    pub const TM75: Datum = Datum::new(Ellipsoid::AIRY_MODIFIED, Some(Helmert::WGS84_TO_TM75));
+    pub const CH1903: Datum = Datum::new(Ellipsoid::BESSEL_1841, Some(Helmert::WGS84_TO_CH1903));
+    pub const CH1903PLUS: Datum =
+        Datum::new(Ellipsoid::BESSEL_1841, Some(Helmert::WGS84_TO_CH1903PLUS));
+
+    pub const fn new(ellipsoid: Ellipsoid, shift: Option<Helmert>) -> Self {
+        Self { ellipsoid, shift }
+    }
+
+    pub const fn ellipsoid(&self) -> &Ellipsoid {
+        &self.ellipsoid
+    }
+
+    /// Convert WGS-84 latitude, longitude in decimal degrees to this datum
+    pub fn from_wgs84(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        match &self.shift {
+            Some(helmert) => {
+                helmert.transform_lat_lon(latitude, longitude, &Ellipsoid::WGS84, &self.ellipsoid)
+            }
+            None => (latitude, longitude),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cartesian_round_trip() {
+        let wgs84 = Ellipsoid::WGS84;
+        let xyz = wgs84.lat_lon_to_cartesian(51.5074, -0.1278, 0.0);
+        let (lat, lon) = wgs84.cartesian_to_lat_lon(xyz);
+        assert!((lat - 51.5074).abs() < 1e-9);
+        assert!((lon + 0.1278).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_helmert_round_trip() {
+        let helmert = Helmert::WGS84_TO_OSGB36;
+        let (lat, lon) =
+            helmert.transform_lat_lon(53.0685, -4.0762, &Ellipsoid::WGS84, &Ellipsoid::AIRY_1830);
+        let (lat2, lon2) =
+            helmert
+                .inverse()
+                .transform_lat_lon(lat, lon, &Ellipsoid::AIRY_1830, &Ellipsoid::WGS84);
+        // The inverse is approximate, but good to a few millimetres
+        assert!((lat2 - 53.0685).abs() < 1e-7);
+        assert!((lon2 + 4.0762).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_helmert_wgs84_to_osgb36() {
+        // OSGB36 is roughly 100 m away from WGS-84 in Great Britain
+        let (lat, lon) = Helmert::WGS84_TO_OSGB36.transform_lat_lon(
+            53.0685,
+            -4.0762,
+            &Ellipsoid::WGS84,
+            &Ellipsoid::AIRY_1830,
+        );
+        assert!((lat - 53.068228).abs() < 1e-6);
+        assert!((lon + 4.074996).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_datum_from_wgs84() {
+        assert_eq!(
+            Datum::ETRS89.from_wgs84(53.0685, -4.0762),
+            (53.0685, -4.0762)
+        );
+        let (lat, lon) = Datum::OSGB36.from_wgs84(53.0685, -4.0762);
+        assert!((lat - 53.068228).abs() < 1e-6);
+        assert!((lon + 4.074996).abs() < 1e-6);
+
+        // CH1903 and CH1903+ share the Bessel ellipsoid, and their shifts differ by centimetres
+        let (lv03_lat, lv03_lon) = Datum::CH1903.from_wgs84(46.9480, 7.4474);
+        let (lv95_lat, lv95_lon) = Datum::CH1903PLUS.from_wgs84(46.9480, 7.4474);
+        assert_ne!((lv03_lat, lv03_lon), (lv95_lat, lv95_lon));
+        assert!((lv03_lat - lv95_lat).abs() < 1e-6);
+        assert!((lv03_lon - lv95_lon).abs() < 1e-6);
+    }
+}
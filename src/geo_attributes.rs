@@ -0,0 +1,478 @@
+use std::collections::BTreeMap;
+
+/// Compass points accepted by `heading:`, clockwise from north in steps of 22.5°
+pub const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// Length units accepted by `dim:`, in metres. Longer suffixes first, so "nmi" is not read as "mi".
+const LENGTH_UNITS: &[(&str, f64)] = &[
+    ("nmi", 1852.0),
+    ("km", 1000.0),
+    ("mi", 1609.344),
+    ("ft", 0.3048),
+    ("m", 1.0),
+];
+
+/// The `type:` of a location, see https://en.wikipedia.org/wiki/Template:Coord#type:T
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocationType {
+    Country,
+    Satellite,
+    State,
+    Adm1st,
+    Adm2nd,
+    Adm3rd,
+    City,
+    Isle,
+    Mountain,
+    River,
+    Waterbody,
+    Event,
+    Forest,
+    Glacier,
+    Airport,
+    RailwayStation,
+    Edu,
+    Pass,
+    Camera,
+    Landmark,
+    /// Any other type, as given
+    Other(String),
+}
+
+impl LocationType {
+    pub fn new(name: &str) -> Self {
+        match name {
+            "country" => Self::Country,
+            "satellite" => Self::Satellite,
+            "state" => Self::State,
+            "adm1st" => Self::Adm1st,
+            "adm2nd" => Self::Adm2nd,
+            "adm3rd" => Self::Adm3rd,
+            "city" => Self::City,
+            "isle" => Self::Isle,
+            "mountain" => Self::Mountain,
+            "river" => Self::River,
+            "waterbody" => Self::Waterbody,
+            "event" => Self::Event,
+            "forest" => Self::Forest,
+            "glacier" => Self::Glacier,
+            "airport" => Self::Airport,
+            "railwaystation" => Self::RailwayStation,
+            "edu" => Self::Edu,
+            "pass" => Self::Pass,
+            "camera" => Self::Camera,
+            "landmark" => Self::Landmark,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Country => "country",
+            Self::Satellite => "satellite",
+            Self::State => "state",
+            Self::Adm1st => "adm1st",
+            Self::Adm2nd => "adm2nd",
+            Self::Adm3rd => "adm3rd",
+            Self::City => "city",
+            Self::Isle => "isle",
+            Self::Mountain => "mountain",
+            Self::River => "river",
+            Self::Waterbody => "waterbody",
+            Self::Event => "event",
+            Self::Forest => "forest",
+            Self::Glacier => "glacier",
+            Self::Airport => "airport",
+            Self::RailwayStation => "railwaystation",
+            Self::Edu => "edu",
+            Self::Pass => "pass",
+            Self::Camera => "camera",
+            Self::Landmark => "landmark",
+            Self::Other(name) => name,
+        }
+    }
+
+    /// Default map scale for the type, if there is one
+    pub const fn default_scale(&self) -> Option<i32> {
+        match self {
+            Self::Country | Self::Satellite => Some(10_000_000),
+            Self::State => Some(3_000_000),
+            Self::Adm1st => Some(1_000_000),
+            Self::Adm2nd => Some(300_000),
+            Self::Adm3rd
+            | Self::City
+            | Self::Isle
+            | Self::Mountain
+            | Self::River
+            | Self::Waterbody => Some(100_000),
+            Self::Event | Self::Forest | Self::Glacier => Some(50_000),
+            Self::Airport => Some(30_000),
+            Self::RailwayStation | Self::Edu | Self::Pass | Self::Camera | Self::Landmark => {
+                Some(10_000)
+            }
+            Self::Other(_) => None,
+        }
+    }
+}
+
+/// The `region:` of a location, an ISO 3166-1 alpha-2 country code,
+/// optionally followed by an ISO 3166-2 subdivision (e.g. "US-NY")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    raw: String,
+    country: String,
+    subdivision: Option<String>,
+}
+
+impl Region {
+    /// Returns `None` if there is no country code
+    pub fn new(raw: &str) -> Option<Self> {
+        let (country, subdivision) = match raw.split_once('-') {
+            Some((country, subdivision)) => (country, Some(subdivision)),
+            None => (raw, None),
+        };
+        let country = country.trim().to_uppercase();
+        if country.is_empty() {
+            return None;
+        }
+        Some(Self {
+            raw: raw.to_string(),
+            country,
+            subdivision: subdivision
+                .map(|s| s.trim().to_uppercase())
+                .filter(|s| !s.is_empty()),
+        })
+    }
+
+    /// The region as given
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// The country part, uppercase
+    pub fn country(&self) -> &str {
+        &self.country
+    }
+
+    /// The subdivision part after the country, uppercase
+    pub fn subdivision(&self) -> Option<&str> {
+        self.subdivision.as_deref()
+    }
+}
+
+/// The `globe:` of a location
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Globe {
+    #[default]
+    Earth,
+    Moon,
+    Mars,
+    Venus,
+    Mercury,
+    /// Any other planet or moon, lowercase
+    Other(String),
+}
+
+impl Globe {
+    pub fn new(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "" | "earth" => Self::Earth,
+            "moon" => Self::Moon,
+            "mars" => Self::Mars,
+            "venus" => Self::Venus,
+            "mercury" => Self::Mercury,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// The lowercase name, as used for the logos and the GeoTemplate subpages
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Earth => "earth",
+            Self::Moon => "moon",
+            Self::Mars => "mars",
+            Self::Venus => "venus",
+            Self::Mercury => "mercury",
+            Self::Other(name) => name,
+        }
+    }
+
+    pub const fn is_earth(&self) -> bool {
+        matches!(self, Self::Earth)
+    }
+}
+
+/// The attributes following the coordinates in `params`, e.g. `type:city(7000000)_region:US-NY`
+#[derive(Debug, Clone, Default)]
+pub struct GeoAttributes {
+    location_type: Option<LocationType>,
+    type_arg: Option<String>,
+    scale: Option<f64>,
+    dim: Option<f64>,
+    zoom: Option<f64>,
+    default_scale: Option<i32>,
+    region: Option<Region>,
+    globe: Globe,
+    heading: Option<f64>,
+    elevation: Option<f64>,
+    source: Option<String>,
+    values: BTreeMap<String, String>,
+}
+
+impl GeoAttributes {
+    /// Parse the pieces left over after the coordinates.
+    /// If an attribute is given more than once, the first one is used.
+    pub fn new(pieces: &[String]) -> Self {
+        let mut values = BTreeMap::new();
+        for s in pieces.iter().rev() {
+            if let Some(i) = s.find(':').filter(|&i| i >= 1) {
+                let attr = &s[..i];
+                let mut val = &s[i + 1..];
+
+                // Check for arguments in parentheses (e.g., "type:city(7000000)")
+                if let Some((j, k)) = val.find('(').zip(val.find(')')).filter(|(j, k)| k > j) {
+                    values.insert(format!("arg:{attr}"), val[j + 1..k].to_string());
+                    val = &val[..j];
+                }
+
+                values.insert(attr.to_string(), val.to_string());
+            } else if let Ok(num) = s.parse::<i32>() {
+                // Bare number is treated as scale if not already set
+                if num > 0 && !values.contains_key("scale") {
+                    values.insert("scale".to_string(), num.to_string());
+                }
+            }
+        }
+
+        let non_empty = |key: &str| values.get(key).filter(|v| !v.is_empty());
+        Self {
+            location_type: non_empty("type").map(|t| LocationType::new(t)),
+            type_arg: non_empty("arg:type").cloned(),
+            scale: non_empty("scale")
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|s| *s > 0.0),
+            dim: non_empty("dim")
+                .and_then(|d| Self::parse_length(d))
+                .filter(|d| *d > 0.0),
+            zoom: non_empty("zoom").and_then(|z| z.parse::<f64>().ok()),
+            default_scale: non_empty("default")
+                .and_then(|d| d.parse::<i32>().ok())
+                .filter(|d| *d > 0),
+            region: non_empty("region").and_then(|r| Region::new(r)),
+            globe: non_empty("globe")
+                .map(|g| Globe::new(g))
+                .unwrap_or_default(),
+            heading: non_empty("heading").and_then(|h| Self::parse_heading(h)),
+            elevation: non_empty("elevation")
+                .or_else(|| non_empty("alt"))
+                .and_then(|e| Self::parse_length(e)),
+            source: non_empty("source").cloned(),
+            values,
+        }
+    }
+
+    /// Parse a length into metres, e.g. "1000", "1.5km", "2mi", "500ft", "3nmi" or "-430m".
+    /// Without a unit, the length is in metres.
+    fn parse_length(length: &str) -> Option<f64> {
+        let length = length.trim().to_lowercase();
+        let (number, factor) = LENGTH_UNITS
+            .iter()
+            .find_map(|(unit, factor)| Some((length.strip_suffix(unit)?, *factor)))
+            .unwrap_or((&length, 1.0));
+        number
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| n * factor)
+    }
+
+    /// Parse a `heading:` value into degrees clockwise from north, e.g. "45" or "NE"
+    fn parse_heading(heading: &str) -> Option<f64> {
+        let heading = heading.trim().to_uppercase();
+        let degrees = match COMPASS_POINTS.iter().position(|p| *p == heading) {
+            Some(i) => i as f64 * 22.5,
+            None => heading.parse::<f64>().ok().filter(|h| h.is_finite())?,
+        };
+        Some(degrees.rem_euclid(360.0))
+    }
+
+    /// The raw value of any attribute, including unknown ones and `arg:<key>` for arguments
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    pub const fn location_type(&self) -> Option<&LocationType> {
+        self.location_type.as_ref()
+    }
+
+    /// The argument of the type, e.g. the population in `type:city(7000000)`
+    pub fn type_arg(&self) -> Option<&str> {
+        self.type_arg.as_deref()
+    }
+
+    /// The map scale, if positive
+    pub const fn scale(&self) -> Option<f64> {
+        self.scale
+    }
+
+    pub const fn set_scale(&mut self, scale: f64) {
+        self.scale = Some(scale);
+    }
+
+    /// The diameter of the object in metres
+    pub const fn dim(&self) -> Option<f64> {
+        self.dim
+    }
+
+    /// The deprecated nlwiki zoom level
+    pub const fn zoom(&self) -> Option<f64> {
+        self.zoom
+    }
+
+    /// The scale to use without `scale:`, `dim:` or `zoom:`
+    pub const fn default_scale(&self) -> Option<i32> {
+        self.default_scale
+    }
+
+    pub const fn region(&self) -> Option<&Region> {
+        self.region.as_ref()
+    }
+
+    pub const fn globe(&self) -> &Globe {
+        &self.globe
+    }
+
+    /// The heading in degrees clockwise from north
+    pub const fn heading(&self) -> Option<f64> {
+        self.heading
+    }
+
+    /// The elevation above sea level in metres, from `elevation:` or `alt:`
+    pub const fn elevation(&self) -> Option<f64> {
+        self.elevation
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(params: &str) -> GeoAttributes {
+        let pieces: Vec<String> = params.split('_').map(|s| s.to_string()).collect();
+        GeoAttributes::new(&pieces)
+    }
+
+    #[test]
+    fn test_typed_attributes() {
+        let attr = attributes("type:city(7000000)_region:US-NY_source:enwiki_heading:NE_foo:bar");
+        assert_eq!(attr.location_type(), Some(&LocationType::City));
+        assert_eq!(attr.type_arg(), Some("7000000"));
+        assert_eq!(attr.region().unwrap().country(), "US");
+        assert_eq!(attr.region().unwrap().subdivision(), Some("NY"));
+        assert_eq!(attr.region().unwrap().raw(), "US-NY");
+        assert_eq!(attr.source(), Some("enwiki"));
+        assert_eq!(attr.heading(), Some(45.0));
+        assert_eq!(attr.globe(), &Globe::Earth);
+        assert_eq!(attr.scale(), None);
+        // Unknown attributes remain accessible
+        assert_eq!(attr.get("foo"), Some("bar"));
+        assert_eq!(attr.get("type"), Some("city"));
+        assert_eq!(attr.get("arg:type"), Some("7000000"));
+    }
+
+    #[test]
+    fn test_location_type() {
+        assert_eq!(
+            LocationType::new("railwaystation").as_str(),
+            "railwaystation"
+        );
+        assert_eq!(
+            LocationType::new("country").default_scale(),
+            Some(10_000_000)
+        );
+        assert_eq!(LocationType::new("landmark").default_scale(), Some(10_000));
+        let other = LocationType::new("City");
+        assert_eq!(other, LocationType::Other("City".to_string()));
+        assert_eq!(other.as_str(), "City");
+        assert_eq!(other.default_scale(), None);
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(attributes("100000").scale(), Some(100_000.0));
+        assert_eq!(attributes("scale:5000_100000").scale(), Some(5_000.0));
+        assert_eq!(attributes("100000_scale:5000").scale(), Some(5_000.0));
+        assert_eq!(attributes("scale:0").scale(), None);
+        assert_eq!(attributes("scale:abc").scale(), None);
+        assert_eq!(attributes("default:30000").default_scale(), Some(30_000));
+    }
+
+    #[test]
+    fn test_dim() {
+        assert_eq!(attributes("dim:1000").dim(), Some(1000.0));
+        assert_eq!(attributes("dim:1000m").dim(), Some(1000.0));
+        assert_eq!(attributes("dim:1km").dim(), Some(1000.0));
+        assert_eq!(attributes("dim:1.5km").dim(), Some(1500.0));
+        assert_eq!(attributes("dim:2mi").dim(), Some(3218.688));
+        assert_eq!(attributes("dim:500ft").dim(), Some(152.4));
+        assert_eq!(attributes("dim:3nmi").dim(), Some(5556.0));
+        assert_eq!(attributes("dim:0.5KM").dim(), Some(500.0));
+        assert_eq!(attributes("dim:abc").dim(), None);
+        assert_eq!(attributes("dim:km").dim(), None);
+        assert_eq!(attributes("dim:0").dim(), None);
+        assert_eq!(attributes("dim:-5m").dim(), None);
+        assert_eq!(attributes("dim:1.5furlong").dim(), None);
+    }
+
+    #[test]
+    fn test_elevation() {
+        assert_eq!(attributes("elevation:8848").elevation(), Some(8848.0));
+        assert_eq!(attributes("elevation:8848m").elevation(), Some(8848.0));
+        assert_eq!(attributes("alt:1000ft").elevation(), Some(304.8));
+        assert_eq!(attributes("alt:-430").elevation(), Some(-430.0));
+        assert_eq!(attributes("elevation:12_alt:34").elevation(), Some(12.0));
+        assert_eq!(attributes("alt:high").elevation(), None);
+    }
+
+    #[test]
+    fn test_first_attribute_wins() {
+        let attr = attributes("region:JP-13_region:#regional_globe:Moon_globe:Mars");
+        assert_eq!(attr.region().unwrap().country(), "JP");
+        assert_eq!(attr.globe(), &Globe::Moon);
+        assert_eq!(attr.get("globe"), Some("Moon"));
+    }
+
+    #[test]
+    fn test_region() {
+        assert_eq!(Region::new("nz").unwrap().country(), "NZ");
+        assert_eq!(Region::new("nz").unwrap().subdivision(), None);
+        assert_eq!(Region::new("GB-LND").unwrap().subdivision(), Some("LND"));
+        assert!(Region::new("-NY").is_none());
+        assert!(Region::new(" ").is_none());
+    }
+
+    #[test]
+    fn test_globe() {
+        assert!(Globe::new("").is_earth());
+        assert!(Globe::new("Earth").is_earth());
+        assert_eq!(Globe::new("Venus"), Globe::Venus);
+        assert_eq!(Globe::new("Ganymede").as_str(), "ganymede");
+    }
+
+    #[test]
+    fn test_heading() {
+        assert_eq!(attributes("heading:N").heading(), Some(0.0));
+        assert_eq!(attributes("heading:wsw").heading(), Some(247.5));
+        assert_eq!(attributes("heading:-90").heading(), Some(270.0));
+        assert_eq!(attributes("heading:400").heading(), Some(40.0));
+        assert_eq!(attributes("heading:up").heading(), None);
+    }
+}
@@ -1,7 +1,12 @@
 use anyhow::{Result, anyhow};
-use std::collections::HashMap;
 
+use crate::geo_attributes::{GeoAttributes, Globe};
+use crate::geo_param_error::GeoParamError;
 use crate::min_sec_result::MinSecResult;
+use crate::traverse_mercator::TransverseMercator;
+
+/// Mean length of one degree of latitude, in metres
+pub(crate) const METRES_PER_DEGREE: f64 = 111_320.0;
 
 /// Parse geographic parameters
 #[derive(Debug, Clone, Default)]
@@ -12,46 +17,219 @@ pub struct GeoParam {
     londeg_min: f64,
     latdeg_max: f64,
     londeg_max: f64,
+    lat_precision: f64,
+    lon_precision: f64,
+    attributes: GeoAttributes,
+    token_count: usize,
     pieces: Vec<String>,
     coor: Vec<String>,
 }
 
 impl GeoParam {
     /// Constructor: Read coordinates, and if there is a range, read the range
-    pub fn new(param: &str) -> Result<Self> {
+    pub fn new(param: &str) -> Result<Self, GeoParamError> {
         // Replace underscores with spaces and split
         let pieces: Vec<String> = param
             .replace('_', " ")
             .split_whitespace()
+            .map(|s| s.to_string())
+            .collect();
+        let pieces: Vec<String> = Self::normalize_pieces(pieces)
+            .into_iter()
             .map(|s| {
                 // Only replace 'O' with 'E' for standalone direction indicators
                 // (German "Ost" for East), not in attribute values like region:JO
                 if s == "O" || s == "o" {
                     "E".to_string()
                 } else {
-                    s.to_string()
+                    s
                 }
             })
             .collect();
 
         let mut geo = Self {
+            token_count: pieces.len(),
             pieces,
             ..Default::default()
         };
 
         geo.get_coor()?;
-        geo.init_min_max();
+        let start = (geo.latdeg, geo.londeg);
 
         // Handle coordinate ranges (e.g., "40 N 74 W to 41 N 73 W")
-        if geo.pieces.first().map(|s| s.as_str()) == Some("to") {
+        let end = if geo.pieces.first().map(|s| s.as_str()) == Some("to") {
             geo.pieces.remove(0);
             geo.get_coor()?;
+            Some((geo.latdeg, geo.londeg))
+        } else {
+            None
+        };
+
+        // The attributes follow the coordinates, and decide the globe
+        geo.attributes = GeoAttributes::new(&geo.pieces);
+
+        (geo.latdeg, geo.londeg) = (start.0, geo.normalize_globe_longitude(start.1));
+        geo.init_min_max();
+        if let Some((latdeg, londeg)) = end {
+            (geo.latdeg, geo.londeg) = (latdeg, geo.normalize_globe_longitude(londeg));
             geo.update_range_bounds();
         }
-
         Ok(geo)
     }
 
+    /// Longitudes are normalized for Earth only; other globes keep their 0-360 conventions
+    fn normalize_globe_longitude(&self, lon: f64) -> f64 {
+        if self.is_earth() {
+            Self::normalize_longitude(lon)
+        } else {
+            lon
+        }
+    }
+
+    /// Normalize free-form coordinates (e.g., "40°42′46″N", "N 40.7128, W 74.006", "40,7128 -74,006")
+    /// in the pieces before the attributes, so they can be parsed by `parse_coordinate_format`
+    fn normalize_pieces(mut pieces: Vec<String>) -> Vec<String> {
+        let attr_start = pieces
+            .iter()
+            .position(|p| p.contains(':'))
+            .unwrap_or(pieces.len());
+        let attributes = pieces.split_off(attr_start);
+
+        let mut ret = vec![];
+        for (i, part) in pieces.split(|p| p == "to").enumerate() {
+            if i > 0 {
+                ret.push("to".to_string());
+            }
+            ret.append(&mut Self::normalize_coordinate(&part.join(" ")));
+        }
+        ret.extend(attributes);
+        ret
+    }
+
+    /// Normalize a single free-form coordinate into space-separated pieces
+    fn normalize_coordinate(coordinate: &str) -> Vec<String> {
+        // Degree symbols, and Unicode and ASCII primes, separate numbers
+        let text: String = coordinate
+            .chars()
+            .map(|c| match c {
+                '°' | 'º' | '˚' | '′' | '\'' | '’' | '‘' | 'ʹ' | '″' | '"' | '”' | '“' | 'ʺ' => {
+                    ' '
+                }
+                '−' => '-',
+                c => c,
+            })
+            .collect();
+        if text.contains(';') {
+            return text.split_whitespace().map(|s| s.to_string()).collect();
+        }
+
+        // Without any decimal points, a comma between digits is a decimal comma.
+        // All other commas separate latitude and longitude.
+        let has_decimal_point = text.contains('.');
+        let chars: Vec<char> = text.chars().collect();
+        let separated: String = chars
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                if c != ',' {
+                    return c;
+                }
+                let between_digits = i > 0
+                    && chars[i - 1].is_ascii_digit()
+                    && chars.get(i + 1).is_some_and(char::is_ascii_digit);
+                if between_digits && !has_decimal_point {
+                    '.'
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+
+        // Split hemispheres from numbers (e.g., "46N" or "N40.7128")
+        let mut pieces: Vec<String> = vec![];
+        for piece in separated.split_whitespace() {
+            let suffix = piece.split_at_checked(piece.len().saturating_sub(1));
+            let prefix = piece.split_at_checked(1);
+            if let Some((number, hemisphere)) =
+                suffix.filter(|(n, h)| Self::is_number(n) && Self::is_hemisphere(h))
+            {
+                pieces.push(number.to_string());
+                pieces.push(hemisphere.to_string());
+            } else if let Some((hemisphere, number)) =
+                prefix.filter(|(h, n)| Self::is_hemisphere(h) && Self::is_number(n))
+            {
+                pieces.push(hemisphere.to_string());
+                pieces.push(number.to_string());
+            } else {
+                pieces.push(piece.to_string());
+            }
+        }
+
+        Self::move_hemisphere_prefixes(&pieces)
+            .or_else(|| Self::add_missing_hemispheres(&pieces))
+            .unwrap_or(pieces)
+    }
+
+    /// Move hemisphere prefixes behind the numbers (e.g., "N 40 42 W 74 0" => "40 42 N 74 0 W")
+    fn move_hemisphere_prefixes(pieces: &[String]) -> Option<Vec<String>> {
+        if !pieces.first().is_some_and(|p| Self::is_hemisphere(p)) {
+            return None;
+        }
+        let mut ret = vec![];
+        let mut hemisphere = None;
+        let mut numbers = 0;
+        for piece in pieces {
+            if Self::is_hemisphere(piece) {
+                if let Some(h) = hemisphere.replace(piece) {
+                    if numbers == 0 {
+                        return None;
+                    }
+                    ret.push(h.to_string());
+                }
+                numbers = 0;
+            } else if Self::is_number(piece) && numbers < 3 {
+                ret.push(piece.to_string());
+                numbers += 1;
+            } else {
+                return None;
+            }
+        }
+        if numbers == 0 {
+            return None;
+        }
+        ret.push(hemisphere?.to_string());
+        Some(ret)
+    }
+
+    /// Signed decimals or DMS values without hemispheres (e.g., "40.7128 -74.006")
+    fn add_missing_hemispheres(pieces: &[String]) -> Option<Vec<String>> {
+        if !pieces.iter().all(|p| Self::is_number(p)) {
+            return None;
+        }
+        match pieces.len() {
+            2 => Some(vec![format!("{};{}", pieces[0], pieces[1])]),
+            4 | 6 => {
+                let (lat, lon) = pieces.split_at(pieces.len() / 2);
+                let mut ret = lat.to_vec();
+                ret.push("N".to_string());
+                ret.extend_from_slice(lon);
+                ret.push("E".to_string());
+                Some(ret)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_hemisphere(s: &str) -> bool {
+        matches!(s, "N" | "S" | "E" | "W" | "O" | "n" | "s" | "e" | "w" | "o")
+    }
+
+    fn is_number(s: &str) -> bool {
+        let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+        digits.chars().any(|c| c.is_ascii_digit())
+            && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+    }
+
     /// Initialize min/max bounds from current coordinates
     const fn init_min_max(&mut self) {
         self.latdeg_min = self.latdeg;
@@ -74,12 +252,60 @@ impl GeoParam {
             self.londeg_max = self.londeg;
         }
 
+        // A range spanning more than half the globe is taken to cross the antimeridian,
+        // so the minimum becomes the western edge and the maximum the eastern edge
+        if self.londeg_max - self.londeg_min > 180.0 {
+            std::mem::swap(&mut self.londeg_min, &mut self.londeg_max);
+        }
+
         // Set coordinates to center of range
         self.latdeg = (self.latdeg_max + self.latdeg_min) / 2.0;
-        self.londeg = (self.londeg_max + self.londeg_min) / 2.0;
+        self.londeg = self.londeg_min + self.lon_span() / 2.0;
+        if self.londeg > 180.0 {
+            self.londeg -= 360.0;
+        }
         self.coor.clear();
     }
 
+    /// Whether the coordinates are a range (e.g., "40 N 74 W to 41 N 73 W")
+    pub const fn is_range(&self) -> bool {
+        self.coor.is_empty()
+    }
+
+    /// Southern edge of a range, or the latitude of a single point
+    pub const fn latdeg_min(&self) -> f64 {
+        self.latdeg_min
+    }
+
+    /// Northern edge of a range, or the latitude of a single point
+    pub const fn latdeg_max(&self) -> f64 {
+        self.latdeg_max
+    }
+
+    /// Western edge of a range, or the longitude of a single point.
+    /// This is larger than `londeg_max` if the range crosses the antimeridian.
+    pub const fn londeg_min(&self) -> f64 {
+        self.londeg_min
+    }
+
+    /// Eastern edge of a range, or the longitude of a single point
+    pub const fn londeg_max(&self) -> f64 {
+        self.londeg_max
+    }
+
+    /// Width of a range in degrees of longitude, eastwards from the western edge
+    pub fn lon_span(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.londeg_max - self.londeg_min + 360.0
+        } else {
+            self.londeg_max - self.londeg_min
+        }
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.londeg_min > self.londeg_max
+    }
+
     pub const fn latdeg(&self) -> f64 {
         self.latdeg
     }
@@ -88,26 +314,94 @@ impl GeoParam {
         self.londeg
     }
 
-    /// Parse a piece as f64, defaulting to 0.0
-    fn parse_piece(&mut self) -> f64 {
-        self.pieces.remove(0).parse().unwrap_or(0.0)
+    /// The globe from the `globe:` attribute, Earth if not given
+    pub const fn globe(&self) -> &Globe {
+        self.attributes.globe()
+    }
+
+    pub const fn is_earth(&self) -> bool {
+        self.globe().is_earth()
+    }
+
+    /// The attributes following the coordinates
+    pub const fn attributes(&self) -> &GeoAttributes {
+        &self.attributes
+    }
+
+    /// Longitude in [0, 360), east-positive, as used for most planets and moons
+    pub fn londeg_east_360(&self) -> f64 {
+        self.londeg.rem_euclid(360.0)
+    }
+
+    /// Longitude in [0, 360), west-positive, as used for Mars and some other planets
+    pub fn londeg_west_360(&self) -> f64 {
+        (-self.londeg).rem_euclid(360.0)
+    }
+
+    /// Precision of the input in degrees, i.e. the granularity of the coarser coordinate.
+    /// For example, 0.1 for "40.7 N 74.0 W", or 1/60 for "40 42 N 74 0 W".
+    pub const fn precision(&self) -> f64 {
+        self.lat_precision.max(self.lon_precision)
+    }
+
+    /// Precision of the input in metres on the ground
+    pub fn precision_m(&self) -> f64 {
+        let lon_precision = self.lon_precision * self.latdeg.to_radians().cos().abs();
+        self.lat_precision.max(lon_precision) * METRES_PER_DEGREE
+    }
+
+    /// Precision of a number, multiplied by the unit of the number in degrees
+    fn piece_precision(piece: &str, unit: f64) -> f64 {
+        let decimals = piece
+            .split_once('.')
+            .map(|(_, fraction)| fraction.len())
+            .unwrap_or(0);
+        unit * 10_f64.powi(-(decimals as i32))
+    }
+
+    /// Parse a piece as f64
+    fn parse_piece(&mut self) -> Result<f64, GeoParamError> {
+        let token = self.token_count - self.pieces.len() + 1;
+        let piece = self.pieces.remove(0);
+        piece
+            .parse()
+            .ok()
+            .filter(|_| Self::is_number(&piece))
+            .ok_or(GeoParamError::UnrecognizedFormat {
+                token,
+                value: Some(piece),
+            })
     }
 
     /// Get a set of coordinates from parameters
-    fn get_coor(&mut self) -> Result<()> {
+    fn get_coor(&mut self) -> Result<(), GeoParamError> {
         if self.pieces.is_empty() {
-            return Err(anyhow!("No coordinates provided"));
+            return Err(GeoParamError::MissingCoordinates);
         }
 
         let (lat_ns, lon_ew, latmin, lonmin, latsec, lonsec) = self.parse_coordinate_format()?;
 
-        self.validate_ranges(latmin, lonmin, latsec, lonsec)?;
+        Self::validate_minutes_seconds(latmin, latsec)?;
+        Self::validate_minutes_seconds(lonmin, lonsec)?;
         self.convert_to_decimal_degrees(&lat_ns, &lon_ew, latmin, lonmin, latsec, lonsec);
+        self.validate_degrees()?;
         Ok(())
     }
 
+    /// Normalize a longitude to (-180, 180]
+    pub fn normalize_longitude(lon: f64) -> f64 {
+        if lon > -180.0 && lon <= 180.0 {
+            // Avoid rounding errors for the common case
+            return lon;
+        }
+        let lon = lon.rem_euclid(360.0);
+        if lon > 180.0 { lon - 360.0 } else { lon }
+    }
+
     /// Parse coordinates based on format and return direction indicators and minute/second values
-    fn parse_coordinate_format(&mut self) -> Result<(String, String, f64, f64, f64, f64)> {
+    fn parse_coordinate_format(
+        &mut self,
+    ) -> Result<(String, String, f64, f64, f64, f64), GeoParamError> {
         let mut lat_ns = "N".to_string();
         let mut lon_ew = "E".to_string();
         let mut latmin = 0.0;
@@ -115,18 +409,31 @@ impl GeoParam {
         let mut latsec = 0.0;
         let mut lonsec = 0.0;
 
+        // Check for grid references (e.g., "utm:32U 461344 5481745", "osgb:TQ3003580414")
+        let (lat_precision, lon_precision);
+        if let Some((latdeg, londeg, precision_m)) = self.parse_grid_reference()? {
+            self.latdeg = latdeg;
+            self.londeg = londeg;
+            self.coor = vec![format!("{latdeg:.6}"), format!("{londeg:.6}")];
+            lat_precision = precision_m / METRES_PER_DEGREE;
+            lon_precision = lat_precision / latdeg.to_radians().cos().max(0.01);
+        }
         // Check for semicolon-separated format (e.g., "40.7128;-74.0060")
-        if let Some(i) = self.pieces[0].find(';') {
+        else if let Some(i) = self.pieces[0].find(';') {
             let piece = self.pieces.remove(0);
+            lat_precision = Self::piece_precision(&piece[..i], 1.0);
+            lon_precision = Self::piece_precision(&piece[i + 1..], 1.0);
             self.latdeg = piece[..i].parse().unwrap_or(0.0);
             self.londeg = piece[i + 1..].parse().unwrap_or(0.0);
             self.coor = vec![self.latdeg.to_string(), self.londeg.to_string()];
         }
         // Degrees only format (e.g., "40 N 74 W")
         else if self.pieces.len() >= 4 && Self::is_coor(&self.pieces[1], &self.pieces[3]) {
-            self.latdeg = self.parse_piece();
+            lat_precision = Self::piece_precision(&self.pieces[0], 1.0);
+            lon_precision = Self::piece_precision(&self.pieces[2], 1.0);
+            self.latdeg = self.parse_piece()?;
             lat_ns = self.pieces.remove(0);
-            self.londeg = self.parse_piece();
+            self.londeg = self.parse_piece()?;
             lon_ew = self.pieces.remove(0);
             self.coor = vec![
                 self.latdeg.to_string(),
@@ -137,11 +444,13 @@ impl GeoParam {
         }
         // Degrees + minutes format (e.g., "40 30 N 74 0 W")
         else if self.pieces.len() >= 6 && Self::is_coor(&self.pieces[2], &self.pieces[5]) {
-            self.latdeg = self.parse_piece();
-            latmin = self.parse_piece();
+            lat_precision = Self::piece_precision(&self.pieces[1], 1.0 / 60.0);
+            lon_precision = Self::piece_precision(&self.pieces[4], 1.0 / 60.0);
+            self.latdeg = self.parse_piece()?;
+            latmin = self.parse_piece()?;
             lat_ns = self.pieces.remove(0);
-            self.londeg = self.parse_piece();
-            lonmin = self.parse_piece();
+            self.londeg = self.parse_piece()?;
+            lonmin = self.parse_piece()?;
             lon_ew = self.pieces.remove(0);
             self.coor = vec![
                 self.latdeg.to_string(),
@@ -154,13 +463,15 @@ impl GeoParam {
         }
         // Degrees + minutes + seconds format (e.g., "40 30 45 N 74 0 21 W")
         else if self.pieces.len() >= 8 && Self::is_coor(&self.pieces[3], &self.pieces[7]) {
-            self.latdeg = self.parse_piece();
-            latmin = self.parse_piece();
-            latsec = self.parse_piece();
+            lat_precision = Self::piece_precision(&self.pieces[2], 1.0 / 3600.0);
+            lon_precision = Self::piece_precision(&self.pieces[6], 1.0 / 3600.0);
+            self.latdeg = self.parse_piece()?;
+            latmin = self.parse_piece()?;
+            latsec = self.parse_piece()?;
             lat_ns = self.pieces.remove(0);
-            self.londeg = self.parse_piece();
-            lonmin = self.parse_piece();
-            lonsec = self.parse_piece();
+            self.londeg = self.parse_piece()?;
+            lonmin = self.parse_piece()?;
+            lonsec = self.parse_piece()?;
             lon_ew = self.pieces.remove(0);
             self.coor = vec![
                 self.latdeg.to_string(),
@@ -173,25 +484,136 @@ impl GeoParam {
                 lon_ew.clone(),
             ];
         } else {
-            return Err(anyhow!("Unrecognized format"));
+            return Err(self.unrecognized_format());
         }
 
+        // For ranges, keep the coarser precision of both ends
+        self.lat_precision = self.lat_precision.max(lat_precision);
+        self.lon_precision = self.lon_precision.max(lon_precision);
+
         Ok((lat_ns, lon_ew, latmin, lonmin, latsec, lonsec))
     }
 
-    /// Validate coordinate ranges
-    fn validate_ranges(&self, latmin: f64, lonmin: f64, latsec: f64, lonsec: f64) -> Result<()> {
-        let valid_degree_range = |deg: f64, max: f64| (-max..=max).contains(&deg);
-        let valid_minsec_range = |val: f64| (0.0..=60.0).contains(&val);
+    /// Find the first piece that can not be part of a coordinate, for the error message
+    fn unrecognized_format(&self) -> GeoParamError {
+        let consumed = self.token_count - self.pieces.len();
+        let coordinates = self
+            .pieces
+            .iter()
+            .take_while(|p| !p.contains(':') && p.as_str() != "to");
+        let (count, offending) = coordinates.fold((0, None), |(count, offending), p| {
+            let valid = Self::is_number(p) || Self::is_hemisphere(p);
+            match offending {
+                None if !valid => (count + 1, Some((count, p))),
+                _ => (count + 1, offending),
+            }
+        });
+        match offending {
+            // Attributes only, or nothing after "to"
+            None if count == 0 => GeoParamError::MissingCoordinates,
+            Some((i, piece)) => GeoParamError::UnrecognizedFormat {
+                token: consumed + i + 1,
+                value: Some(piece.to_string()),
+            },
+            None => GeoParamError::UnrecognizedFormat {
+                token: consumed + count,
+                value: None,
+            },
+        }
+    }
 
-        if !valid_degree_range(self.latdeg, 90.0)
-            || !valid_degree_range(self.londeg, 360.0)
-            || !valid_minsec_range(latmin)
-            || !valid_minsec_range(lonmin)
-            || !valid_minsec_range(latsec)
-            || !valid_minsec_range(lonsec)
-        {
-            return Err(anyhow!("Out of range"));
+    /// Parse a grid reference with a `utm:`, `mgrs:`, `osgb:` or `ch1903:` prefix,
+    /// and convert it to latitude, longitude in decimal degrees, and the precision in metres.
+    /// Returns `None` if the first piece is not a grid reference.
+    fn parse_grid_reference(&mut self) -> Result<Option<(f64, f64, f64)>, GeoParamError> {
+        let Some((system, value)) = self.pieces[0].split_once(':') else {
+            return Ok(None);
+        };
+        let system = system.to_lowercase();
+        if !["utm", "mgrs", "osgb", "ch1903"].contains(&system.as_str()) {
+            return Ok(None);
+        }
+        let value = value.to_string();
+        self.pieces.remove(0);
+
+        let invalid = || GeoParamError::InvalidGridReference(system.clone());
+        let tm = TransverseMercator::default();
+        let ret = match system.as_str() {
+            "utm" => {
+                let (easting, northing) = self.parse_easting_northing().ok_or_else(invalid)?;
+                tm.utm_to_lat_lon(easting, northing, &value)
+            }
+            "mgrs" => tm.mgrs_to_lat_lon(&value),
+            "osgb" => {
+                let (easting, northing) = if value.is_empty() {
+                    self.parse_easting_northing()
+                } else {
+                    TransverseMercator::osgb36_ref_to_easting_northing(&value)
+                }
+                .ok_or_else(invalid)?;
+                Some(TransverseMercator::osgb36_to_lat_lon(easting, northing))
+            }
+            _ => {
+                let (easting, northing) = if value.is_empty() {
+                    self.parse_easting_northing()
+                } else {
+                    let easting = value.parse::<f64>().ok();
+                    let northing = self.parse_easting_northing_piece();
+                    easting.zip(northing)
+                }
+                .ok_or_else(invalid)?;
+                Some(TransverseMercator::ch1903_to_lat_lon(easting, northing))
+            }
+        };
+        // Lettered references give the precision by their number of digits, e.g. TQ3080 is 1 km
+        let lettered = system == "mgrs" || (system == "osgb" && !value.is_empty());
+        let precision_m = if lettered {
+            let digits = value
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .chars()
+                .filter(char::is_ascii_digit)
+                .count();
+            10_f64.powi(5 - (digits / 2) as i32)
+        } else {
+            1.0
+        };
+        ret.map(|(lat, lon)| Some((lat, lon, precision_m)))
+            .ok_or_else(invalid)
+    }
+
+    /// Read Easting and Northing from the next two pieces
+    fn parse_easting_northing(&mut self) -> Option<(f64, f64)> {
+        let easting = self.parse_easting_northing_piece()?;
+        let northing = self.parse_easting_northing_piece()?;
+        Some((easting, northing))
+    }
+
+    /// Read a single Easting or Northing value in metres
+    fn parse_easting_northing_piece(&mut self) -> Option<f64> {
+        let value = self.pieces.first()?.parse::<f64>().ok()?;
+        self.pieces.remove(0);
+        Some(value)
+    }
+
+    /// Validate minutes and seconds, which must be in [0, 60)
+    fn validate_minutes_seconds(min: f64, sec: f64) -> Result<(), GeoParamError> {
+        let valid = |val: f64| (0.0..60.0).contains(&val);
+        if !valid(min) {
+            return Err(GeoParamError::MinutesOutOfRange(min));
+        }
+        if !valid(sec) {
+            return Err(GeoParamError::SecondsOutOfRange(sec));
+        }
+        Ok(())
+    }
+
+    /// Validate the converted decimal degrees
+    fn validate_degrees(&self) -> Result<(), GeoParamError> {
+        if !(-90.0..=90.0).contains(&self.latdeg) {
+            return Err(GeoParamError::LatitudeOutOfRange(self.latdeg));
+        }
+        if !(-360.0..=360.0).contains(&self.londeg) {
+            return Err(GeoParamError::LongitudeOutOfRange(self.londeg));
         }
         Ok(())
     }
@@ -251,33 +673,6 @@ impl GeoParam {
         format!("{}{} {}{}", outlat, latdms.ns(), outlon, londms.ew())
     }
 
-    /// Get the additional attributes in an associative array (HashMap)
-    pub fn get_attr(&mut self) -> HashMap<String, String> {
-        let mut attributes = HashMap::new();
-
-        while let Some(s) = self.pieces.pop() {
-            if let Some(i) = s.find(':').filter(|&i| i >= 1) {
-                let attr = &s[..i];
-                let mut val = &s[i + 1..];
-
-                // Check for arguments in parentheses (e.g., "type:city(7000000)")
-                if let Some((j, k)) = val.find('(').zip(val.find(')')).filter(|(j, k)| k > j) {
-                    attributes.insert(format!("arg:{attr}"), val[j + 1..k].to_string());
-                    val = &val[..j];
-                }
-
-                attributes.insert(attr.to_string(), val.to_string());
-            } else if let Ok(num) = s.parse::<i32>() {
-                // Bare number is treated as scale if not already set
-                if num > 0 && !attributes.contains_key("scale") {
-                    attributes.insert("scale".to_string(), num.to_string());
-                }
-            }
-        }
-
-        attributes
-    }
-
     /// Check if strings represent valid N/S and E/W directions
     fn is_coor(ns: &str, ew: &str) -> bool {
         let ns = ns.to_uppercase();
@@ -335,18 +730,16 @@ impl GeoParam {
         &self.coor
     }
 
+    #[cfg(test)]
     pub const fn pieces(&self) -> &Vec<String> {
         &self.pieces
     }
-
-    pub const fn pieces_mut(&mut self) -> &mut Vec<String> {
-        &mut self.pieces
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::geo_attributes::LocationType;
 
     #[test]
     fn test_simple_coordinates() {
@@ -380,6 +773,74 @@ mod tests {
         assert_eq!(geo.londeg, -73.5);
     }
 
+    #[test]
+    fn test_range_antimeridian() {
+        let geo = GeoParam::new("20_S_170_E_to_10_S_170_W").unwrap();
+        assert!(geo.is_range());
+        assert!(geo.crosses_antimeridian());
+        assert_eq!(geo.londeg_min(), 170.0);
+        assert_eq!(geo.londeg_max(), -170.0);
+        assert_eq!(geo.lon_span(), 20.0);
+        assert_eq!(geo.londeg(), 180.0);
+        assert_eq!(geo.latdeg(), -15.0);
+
+        let west = GeoParam::new("10_N_175_W_to_20_N_165_E").unwrap();
+        assert!(west.crosses_antimeridian());
+        assert_eq!(west.londeg_min(), 165.0);
+        assert_eq!(west.londeg_max(), -175.0);
+        assert_eq!(west.londeg(), 175.0);
+
+        let point = GeoParam::new("10_N_175_W").unwrap();
+        assert!(!point.is_range());
+        assert!(!point.crosses_antimeridian());
+        assert_eq!(point.lon_span(), 0.0);
+    }
+
+    #[test]
+    fn test_longitude_normalization() {
+        let cases = [
+            ("10_N_190_E", -170.0),
+            ("10_N_270_W", 90.0),
+            ("10_N_180_W", 180.0),
+            ("10_N_180_E", 180.0),
+            ("10_N_360_E", 0.0),
+            ("10_N_-190_E", 170.0),
+            ("10;350", -10.0),
+            ("10_N_190_E_globe:earth", -170.0),
+            ("10_N_190_E_globe:Earth", -170.0),
+        ];
+        for (params, expected) in cases {
+            let geo = GeoParam::new(params).unwrap();
+            assert!(geo.is_earth(), "{params}");
+            assert_eq!(geo.londeg(), expected, "{params}");
+        }
+
+        let mars = GeoParam::new("9.3_S_322_W_globe:Mars").unwrap();
+        assert!(!mars.is_earth());
+        assert_eq!(mars.globe(), &Globe::Mars);
+        assert_eq!(mars.londeg(), -322.0);
+        assert_eq!(mars.londeg_east_360(), 38.0);
+        assert_eq!(mars.londeg_west_360(), 322.0);
+
+        let venus = GeoParam::new("10.70_S_335.25_E_globe:Venus").unwrap();
+        assert_eq!(venus.londeg(), 335.25);
+        assert_eq!(venus.londeg_east_360(), 335.25);
+        assert_eq!(venus.londeg_west_360(), 24.75);
+
+        let range = GeoParam::new("10_N_170_E_to_20_N_190_E").unwrap();
+        assert!(range.crosses_antimeridian());
+        assert_eq!(range.londeg_max(), -170.0);
+        assert_eq!(range.londeg(), 180.0);
+
+        // The globe follows the attributes: the first one wins, and an empty one is ignored
+        let first = GeoParam::new("10_N_190_E_globe:Mars_globe:Earth").unwrap();
+        assert_eq!(first.globe(), &Globe::Mars);
+        assert_eq!(first.londeg(), 190.0);
+        let empty = GeoParam::new("10_N_190_E_globe:_globe:Mars").unwrap();
+        assert!(empty.is_earth());
+        assert_eq!(empty.londeg(), -170.0);
+    }
+
     #[test]
     fn test_make_position() {
         let position = GeoParam::make_position(40.7128, -74.0060);
@@ -393,7 +854,7 @@ mod tests {
     fn test_out_of_range() {
         match GeoParam::new("91 N 180 E") {
             Ok(_geo) => panic!("This should have failed!"),
-            Err(err) => assert_eq!(err.to_string(), "Out of range"),
+            Err(err) => assert_eq!(err.to_string(), "Latitude 91 is out of range (-90 to 90)"),
         };
     }
 
@@ -430,33 +891,32 @@ mod tests {
 
     #[test]
     fn test_get_attr_with_type() {
-        let mut geo = GeoParam::new("40_N_74_W_type:city").unwrap();
-        let attr = geo.get_attr();
-        assert_eq!(attr.get("type"), Some(&"city".to_string()));
+        let geo = GeoParam::new("40_N_74_W_type:city").unwrap();
+        let attr = geo.attributes();
+        assert_eq!(attr.location_type(), Some(&LocationType::City));
     }
 
     #[test]
     fn test_get_attr_with_type_and_arg() {
-        let mut geo = GeoParam::new("40_N_74_W_type:city(7000000)").unwrap();
-        let attr = geo.get_attr();
-        assert_eq!(attr.get("type"), Some(&"city".to_string()));
-        assert_eq!(attr.get("arg:type"), Some(&"7000000".to_string()));
+        let geo = GeoParam::new("40_N_74_W_type:city(7000000)").unwrap();
+        let attr = geo.attributes();
+        assert_eq!(attr.location_type(), Some(&LocationType::City));
+        assert_eq!(attr.type_arg(), Some("7000000"));
     }
 
     #[test]
     fn test_get_attr_with_scale() {
-        let mut geo = GeoParam::new("40_N_74_W_100000").unwrap();
-        let attr = geo.get_attr();
-        assert_eq!(attr.get("scale"), Some(&"100000".to_string()));
+        let geo = GeoParam::new("40_N_74_W_100000").unwrap();
+        assert_eq!(geo.attributes().scale(), Some(100_000.0));
     }
 
     #[test]
     fn test_get_attr_multiple() {
-        let mut geo = GeoParam::new("40_N_74_W_type:city_region:US-NY_source:enwiki").unwrap();
-        let attr = geo.get_attr();
-        assert_eq!(attr.get("type"), Some(&"city".to_string()));
-        assert_eq!(attr.get("region"), Some(&"US-NY".to_string()));
-        assert_eq!(attr.get("source"), Some(&"enwiki".to_string()));
+        let geo = GeoParam::new("40_N_74_W_type:city_region:US-NY_source:enwiki").unwrap();
+        let attr = geo.attributes();
+        assert_eq!(attr.location_type(), Some(&LocationType::City));
+        assert_eq!(attr.region().unwrap().raw(), "US-NY");
+        assert_eq!(attr.source(), Some("enwiki"));
     }
 
     #[test]
@@ -468,13 +928,13 @@ mod tests {
 
     #[test]
     fn test_region_code_preserved() {
-        let mut geo = GeoParam::new("40_N_74_W_region:JO").unwrap();
-        let attr = geo.get_attr();
-        assert_eq!(attr.get("region"), Some(&"JO".to_string())); // Jordan, not Jersey
+        let geo = GeoParam::new("40_N_74_W_region:JO").unwrap();
+        let region = geo.attributes().region().unwrap();
+        assert_eq!(region.country(), "JO"); // Jordan, not Jersey
 
-        let mut geo2 = GeoParam::new("40_N_74_W_region:CA-ON").unwrap();
-        let attr2 = geo2.get_attr();
-        assert_eq!(attr2.get("region"), Some(&"CA-ON".to_string())); // Ontario, not CA-EN
+        let geo2 = GeoParam::new("40_N_74_W_region:CA-ON").unwrap();
+        let region2 = geo2.attributes().region().unwrap();
+        assert_eq!(region2.subdivision(), Some("ON")); // Ontario, not CA-EN
     }
 
     #[test]
@@ -484,6 +944,153 @@ mod tests {
         assert_eq!(geo.londeg, 149.0);
     }
 
+    #[test]
+    fn test_free_form_coordinates() {
+        let cases = [
+            ("40°42′46″N 74°00′22″W", 40.712778, -74.006111),
+            ("40°42′46″N_74°00′22″W", 40.712778, -74.006111),
+            ("40° 42' 46\" N 74° 0' 22\" W", 40.712778, -74.006111),
+            ("40° 42' 46'' N, 74° 0' 22'' W", 40.712778, -74.006111),
+            ("40º42’46”N 74º00’22”W", 40.712778, -74.006111),
+            ("40°42.767′N 74°0.367′W", 40.712783, -74.006117),
+            ("40.7128°N 74.006°W", 40.7128, -74.006),
+            ("40.7128N 74.006W", 40.7128, -74.006),
+            ("N 40.7128, W 74.006", 40.7128, -74.006),
+            ("N40.7128 W74.006", 40.7128, -74.006),
+            ("N 40° 42′ 46″ W 74° 0′ 22″", 40.712778, -74.006111),
+            ("S 33° 51′ 25″ E 151° 12′ 55″", -33.856944, 151.215278),
+            ("40,7128 -74,006", 40.7128, -74.006),
+            ("40,7128, -74,006", 40.7128, -74.006),
+            ("40.7128, -74.006", 40.7128, -74.006),
+            ("40.7128,-74.006", 40.7128, -74.006),
+            ("40.7128 −74.006", 40.7128, -74.006),
+            ("-33.8568 151.2153", -33.8568, 151.2153),
+            ("40 42 46 -74 0 22", 40.712778, -74.006111),
+            ("52°31′N 13°24′O", 52.516667, 13.4),
+            ("52°31′ n 13°24′ e", 52.516667, 13.4),
+        ];
+        for (input, lat, lon) in cases {
+            let geo = GeoParam::new(input).unwrap();
+            assert!(
+                (geo.latdeg() - lat).abs() < 1e-5,
+                "{input}: {}",
+                geo.latdeg()
+            );
+            assert!(
+                (geo.londeg() - lon).abs() < 1e-5,
+                "{input}: {}",
+                geo.londeg()
+            );
+            assert!(geo.pieces().is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_free_form_coordinates_with_attributes() {
+        let geo = GeoParam::new("40°42′46″N 74°00′22″W type:city(8,804,190)").unwrap();
+        assert!((geo.latdeg() - 40.712778).abs() < 1e-5);
+        let attr = geo.attributes();
+        assert_eq!(attr.location_type(), Some(&LocationType::City));
+        assert_eq!(attr.type_arg(), Some("8,804,190"));
+
+        let range = GeoParam::new("N 40, W 74 to N 41, W 73").unwrap();
+        assert_eq!(range.latdeg(), 40.5);
+        assert_eq!(range.londeg(), -73.5);
+    }
+
+    #[test]
+    fn test_free_form_coordinates_invalid() {
+        assert!(GeoParam::new("40.7128").is_err());
+        assert!(GeoParam::new("N 40.7128").is_err());
+        assert!(GeoParam::new("N W 74.006").is_err());
+        assert!(GeoParam::new("40 42 -74 0").is_ok());
+        // Non-ASCII characters next to numbers must not panic
+        assert!(GeoParam::new("52°31′N 13°24′É").is_err());
+        assert!(GeoParam::new("É52 13").is_err());
+        assert!(GeoParam::new("40 42 46 -74 0").is_err());
+    }
+
+    #[test]
+    fn test_precision() {
+        let cases = [
+            ("40.7128_N_74.0060_W", 0.0001),
+            ("40.7_N_74.0060_W", 0.1),
+            ("40_N_74_W", 1.0),
+            ("40_30_N_74_0_W", 1.0 / 60.0),
+            ("40_30_45_N_74_0_21_W", 1.0 / 3600.0),
+            ("40_30_45.5_N_74_0_21.25_W", 0.1 / 3600.0),
+            ("40.71;-74.006", 0.01),
+            ("40.7128_N_74.0060_W_to_41_N_73_W", 1.0),
+        ];
+        for (params, expected) in cases {
+            let geo = GeoParam::new(params).unwrap();
+            assert!((geo.precision() - expected).abs() < 1e-12, "{params}");
+        }
+
+        let geo = GeoParam::new("0.1_N_0.1_E").unwrap();
+        assert!((geo.precision_m() - 11132.0).abs() < 1e-6);
+        // Longitude degrees are shorter away from the equator
+        let north = GeoParam::new("60.1_N_10_E").unwrap();
+        let expected = 60.1_f64.to_radians().cos() * METRES_PER_DEGREE;
+        assert!((north.precision_m() - expected).abs() < 1e-6);
+
+        let mgrs = GeoParam::new("mgrs:18SUJ2306").unwrap();
+        assert!((mgrs.precision_m() - 1000.0).abs() < 1.0);
+        let osgb = GeoParam::new("osgb:TQ3003580414").unwrap();
+        assert!((osgb.precision_m() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_utm_grid_reference() {
+        let geo = GeoParam::new("utm:32U_461344_5481745_type:landmark").unwrap();
+        assert!((geo.latdeg() - 49.4875).abs() < 1e-3);
+        assert!((geo.londeg() - 8.4661).abs() < 1e-3);
+        assert_eq!(geo.pieces(), &vec!["type:landmark".to_string()]);
+        assert_eq!(geo.coor().len(), 2);
+
+        // Southern hemisphere, from the zone letter
+        let south = GeoParam::new("utm:56H_334900_6252288").unwrap();
+        assert!((south.latdeg() + 33.8568).abs() < 1e-3);
+        assert!((south.londeg() - 151.2153).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_mgrs_grid_reference() {
+        let geo = GeoParam::new("mgrs:18SUJ2348606483").unwrap();
+        assert!((geo.latdeg() - 38.8895).abs() < 1e-4);
+        assert!((geo.londeg() + 77.0352).abs() < 1e-4);
+        assert_eq!(geo.get_markup().unwrap(), "38.889499;-77.035208");
+    }
+
+    #[test]
+    fn test_osgb_grid_reference() {
+        let geo = GeoParam::new("osgb:SH6098654375").unwrap();
+        assert!((geo.latdeg() - 53.0685).abs() < 1e-4);
+        assert!((geo.londeg() + 4.0762).abs() < 1e-4);
+
+        let numeric = GeoParam::new("osgb:_260986_354375").unwrap();
+        assert!((numeric.latdeg() - 53.0685).abs() < 1e-4);
+        assert!((numeric.londeg() + 4.0762).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ch1903_grid_reference() {
+        let geo = GeoParam::new("ch1903:2600000_1200000").unwrap();
+        assert!((geo.latdeg() - 46.9511).abs() < 1e-3);
+        assert!((geo.londeg() - 7.4386).abs() < 1e-3);
+
+        let lv03 = GeoParam::new("CH1903:600000_200000").unwrap();
+        assert!((lv03.latdeg() - 46.9511).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_invalid_grid_reference() {
+        let err = GeoParam::new("mgrs:18SUJ234860648").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid mgrs grid reference");
+        assert!(GeoParam::new("utm:32U_461344").is_err());
+        assert!(GeoParam::new("osgb:TI1234").is_err());
+    }
+
     #[test]
     fn test_no_coordinates_error() {
         match GeoParam::new("") {
@@ -496,7 +1103,62 @@ mod tests {
     fn test_unrecognized_format_error() {
         match GeoParam::new("invalid coordinates here") {
             Ok(_) => panic!("Should have failed"),
-            Err(e) => assert_eq!(e.to_string(), "Unrecognized format"),
+            Err(e) => assert_eq!(
+                e.to_string(),
+                "Unrecognized format at token 1 (\"invalid\")"
+            ),
         }
     }
+
+    #[test]
+    fn test_typed_errors() {
+        let cases = [
+            ("", GeoParamError::MissingCoordinates),
+            ("type:city_region:DE", GeoParamError::MissingCoordinates),
+            ("40_N_74_W_to", GeoParamError::MissingCoordinates),
+            (
+                "40_X_74_W",
+                GeoParamError::UnrecognizedFormat {
+                    token: 2,
+                    value: Some("X".to_string()),
+                },
+            ),
+            (
+                "40_N_74_W_to_41_N_foo_W",
+                GeoParamError::UnrecognizedFormat {
+                    token: 8,
+                    value: Some("foo".to_string()),
+                },
+            ),
+            (
+                "40_N_74_type:city",
+                GeoParamError::UnrecognizedFormat {
+                    token: 3,
+                    value: None,
+                },
+            ),
+            ("91_N_74_W", GeoParamError::LatitudeOutOfRange(91.0)),
+            ("89_60_N_74_0_W", GeoParamError::MinutesOutOfRange(60.0)),
+            ("40_30_N_74_75_W", GeoParamError::MinutesOutOfRange(75.0)),
+            (
+                "40_30_61_N_74_0_0_W",
+                GeoParamError::SecondsOutOfRange(61.0),
+            ),
+            ("40_N_400_W", GeoParamError::LongitudeOutOfRange(-400.0)),
+            ("-95;10", GeoParamError::LatitudeOutOfRange(-95.0)),
+            (
+                "mgrs:18SUJ234",
+                GeoParamError::InvalidGridReference("mgrs".to_string()),
+            ),
+        ];
+        for (params, expected) in cases {
+            assert_eq!(GeoParam::new(params).unwrap_err(), expected, "{params}");
+        }
+
+        // Degrees alone can be in range, but not with the minutes added
+        assert_eq!(
+            GeoParam::new("90_30_N_0_0_E").unwrap_err(),
+            GeoParamError::LatitudeOutOfRange(90.5)
+        );
+    }
 }
@@ -0,0 +1,165 @@
+use std::fmt;
+
+/// Examples of accepted `params` formats, shown on the error page
+const FORMAT_EXAMPLES: &[(&str, &str)] = &[
+    ("40.7128_N_74.006_W", "decimal degrees"),
+    ("40_42_46_N_74_0_22_W", "degrees, minutes, seconds"),
+    ("40_42.767_N_74_0.367_W", "degrees, decimal minutes"),
+    ("40.7128;-74.006", "signed decimal degrees"),
+    ("40_N_74_W_to_41_N_73_W", "a range"),
+    ("utm:18T_583960_4507523", "a UTM grid reference"),
+    (
+        "40.7128_N_74.006_W_type:city_region:US-NY",
+        "with attributes",
+    ),
+];
+
+/// Why `params` could not be parsed into coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoParamError {
+    /// `&params=` is empty or missing
+    MissingParams,
+    /// No coordinates before the attributes, or after "to"
+    MissingCoordinates,
+    /// The coordinates do not match any known format.
+    /// `token` is the 1-based position of the first offending piece, `value` is `None` if
+    /// the coordinates end early.
+    UnrecognizedFormat {
+        token: usize,
+        value: Option<String>,
+    },
+    LatitudeOutOfRange(f64),
+    LongitudeOutOfRange(f64),
+    /// Minutes must be in [0, 60)
+    MinutesOutOfRange(f64),
+    /// Seconds must be in [0, 60)
+    SecondsOutOfRange(f64),
+    /// A `utm:`, `mgrs:`, `osgb:` or `ch1903:` reference that could not be converted
+    InvalidGridReference(String),
+}
+
+impl fmt::Display for GeoParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingParams => write!(f, "No parameters given (&params= is empty or missing)"),
+            Self::MissingCoordinates => write!(f, "No coordinates provided"),
+            Self::UnrecognizedFormat {
+                token,
+                value: Some(value),
+            } => write!(f, "Unrecognized format at token {token} (\"{value}\")"),
+            Self::UnrecognizedFormat { token, value: None } => {
+                write!(
+                    f,
+                    "Unrecognized format: incomplete coordinates after token {token}"
+                )
+            }
+            Self::LatitudeOutOfRange(value) => {
+                write!(f, "Latitude {value} is out of range (-90 to 90)")
+            }
+            Self::LongitudeOutOfRange(value) => {
+                write!(f, "Longitude {value} is out of range (-360 to 360)")
+            }
+            Self::MinutesOutOfRange(value) => {
+                write!(f, "Minutes {value} are out of range (0 to less than 60)")
+            }
+            Self::SecondsOutOfRange(value) => {
+                write!(f, "Seconds {value} are out of range (0 to less than 60)")
+            }
+            Self::InvalidGridReference(system) => write!(f, "Invalid {system} grid reference"),
+        }
+    }
+}
+
+impl std::error::Error for GeoParamError {}
+
+impl GeoParamError {
+    /// A short machine-readable code, as used by the API
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::MissingParams => "missing_params",
+            Self::MissingCoordinates => "missing_coordinates",
+            Self::UnrecognizedFormat { .. } => "unrecognized_format",
+            Self::LatitudeOutOfRange(_) => "latitude_out_of_range",
+            Self::LongitudeOutOfRange(_) => "longitude_out_of_range",
+            Self::MinutesOutOfRange(_) => "minutes_out_of_range",
+            Self::SecondsOutOfRange(_) => "seconds_out_of_range",
+            Self::InvalidGridReference(_) => "invalid_grid_reference",
+        }
+    }
+
+    /// A page explaining what was wrong with `params`, with examples of accepted formats
+    pub fn html_page(&self, params: &str) -> String {
+        let message = html_escape::encode_text(&self.to_string()).to_string();
+        let params = html_escape::encode_text(params).to_string();
+        let examples: String = FORMAT_EXAMPLES
+            .iter()
+            .map(|(example, description)| {
+                format!("<li><code>params={example}</code> &ndash; {description}</li>\n")
+            })
+            .collect();
+        format!(
+            r#"<!DOCTYPE html>
+<html><head>
+<title>GeoHack - Invalid coordinates</title>
+<meta http-equiv="content-type" content="text/html; charset=utf-8" />
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex" />
+<link rel="shortcut icon" href="/geohack/siteicon.png" />
+<link rel="stylesheet" type="text/css" media="screen" href="./main.css" />
+</head>
+<body class="mediawiki skin-modern">
+<div id="mw_header"><h1 id="firstHeading">GeoHack - Invalid coordinates</h1></div>
+<div id="mw_main" style="margin-top:2em;">
+<div class="usermessage" style="background:#c00; color:white;">{message}</div>
+<p>The coordinates in <code>params={params}</code> could not be read.</p>
+<p>Accepted formats include:</p>
+<ul>
+{examples}</ul>
+<p>Underscores and spaces can be used interchangeably. See
+<a href="https://en.wikipedia.org/wiki/Template:Coord">Template:Coord</a> for details.</p>
+</div>
+</body></html>
+"#
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo_param::GeoParam;
+
+    #[test]
+    fn test_error_messages() {
+        let err = GeoParamError::UnrecognizedFormat {
+            token: 2,
+            value: Some("X".to_string()),
+        };
+        assert_eq!(err.to_string(), "Unrecognized format at token 2 (\"X\")");
+        assert_eq!(err.code(), "unrecognized_format");
+        assert_eq!(
+            GeoParamError::LatitudeOutOfRange(91.0).to_string(),
+            "Latitude 91 is out of range (-90 to 90)"
+        );
+        assert_eq!(
+            GeoParamError::MinutesOutOfRange(61.0).to_string(),
+            "Minutes 61 are out of range (0 to less than 60)"
+        );
+    }
+
+    #[test]
+    fn test_html_page() {
+        let html = GeoParamError::MissingCoordinates.html_page("<script>_type:city");
+        assert!(html.contains("No coordinates provided"));
+        assert!(html.contains("params=&lt;script&gt;_type:city"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("params=40_42_46_N_74_0_22_W"));
+    }
+
+    #[test]
+    fn test_format_examples_parse() {
+        for (example, _description) in FORMAT_EXAMPLES {
+            assert!(GeoParam::new(example).is_ok(), "{example}");
+        }
+    }
+}
@@ -3,6 +3,8 @@
  * Released under GPL
  * Converted to Rust 2005 by <Magnus Manske> <magnusmanske@googlemail.com>
 */
+use crate::geo_attributes::Globe;
+use crate::geo_param_error::GeoParamError;
 use crate::map_sources::MapSources;
 use crate::min_sec_result::MinSecResult;
 use crate::query_parameters::QueryParameters;
@@ -10,7 +12,8 @@ use crate::regex_patterns::{
     RE_FIX_LANGUAGE_CODE, RE_INIT_FROM_QUERY, RE_MAKE_LINK, RE_SANITIZE_HTML,
     RE_WIKIPEDIA_LANG_LINK,
 };
-use anyhow::{Result, anyhow};
+use crate::template_lint::TemplateLint;
+use anyhow::Result;
 use std::collections::HashMap;
 
 /// Main GeoHack application struct
@@ -23,7 +26,7 @@ pub struct GeoHack {
     map_sources: MapSources,
     region_name: Option<String>,
     globe: String,
-    nlzoom: String,
+    nlzoom: bool,
     page_content: String,
     logo_urls: HashMap<String, String>,
     actions: String,
@@ -41,7 +44,7 @@ impl GeoHack {
             map_sources: MapSources::default(),
             region_name: None,
             globe: String::new(),
-            nlzoom: String::new(),
+            nlzoom: false,
             page_content: String::new(),
             logo_urls: Self::init_logo_urls(),
             actions: String::new(),
@@ -155,9 +158,7 @@ impl GeoHack {
         self.params = html_escape::encode_text(&params).to_string();
 
         if self.params.is_empty() {
-            return Err(anyhow!(
-                "No parameters given (&params= is empty or missing)"
-            ));
+            return Err(GeoParamError::MissingParams.into());
         }
 
         // Using REFERER as a last resort for pagename
@@ -203,15 +204,14 @@ impl GeoHack {
 
     /// Detect region from parameters
     fn detect_region_zoom_globe(&mut self) {
-        for v in self.map_sources.p().pieces() {
-            if let Some(end) = v.strip_prefix("region:") {
-                self.region_name = Self::process_region_name(end);
-            } else if let Some(end) = v.strip_prefix("globe:") {
-                self.globe = end.to_lowercase();
-            } else if let Some(end) = v.strip_prefix("zoom:") {
-                self.nlzoom = end.to_lowercase();
-            }
-        }
+        let attr = self.map_sources.p().attributes();
+        self.region_name = attr.region().map(|region| region.country().to_string());
+        // Earth uses the main GeoTemplate, so it shares the template cache with no globe
+        self.globe = match attr.globe() {
+            Globe::Earth => String::new(),
+            globe => globe.as_str().to_string(),
+        };
+        self.nlzoom = attr.get("zoom").is_some_and(|zoom| !zoom.is_empty());
     }
 
     /// Build the output HTML
@@ -256,7 +256,7 @@ impl GeoHack {
 <div id="mw_contentwrapper"><div id="mw_content">"#, mytitle, self.lang, mytitle));
 
         // Add zoom warning if needed
-        if !self.nlzoom.is_empty() {
+        if self.nlzoom {
             html.push_str(
                 r#"
 <div class="mw-topboxes">
@@ -340,11 +340,6 @@ Waarschuwing:
         // Build the map sources output
         let processed_content = self.map_sources.build_output(&self.pagename, &self.title)?;
 
-        // Apply ugly hacks
-        let processed_content = processed_content
-            .replace("{nztmeasting}", "0")
-            .replace("{nztmnorthing}", "0");
-
         // Handle localized services
         let mut final_content = processed_content.clone();
         if let Some(region) = &self.region_name {
@@ -364,6 +359,11 @@ Waarschuwing:
         Ok(self.build_output())
     }
 
+    /// List the unresolved placeholders in the template, per GEOTEMPLATE-XX section
+    pub fn lint_template(&mut self) -> Result<TemplateLint> {
+        self.map_sources.lint(&self.pagename, &self.title)
+    }
+
     fn fix_wikipedia_html(&mut self) {
         (self.page_content, self.actions, self.languages) = self.process_wikipedia_page();
     }
@@ -463,22 +463,6 @@ Waarschuwing:
 
         (page, actions, languages)
     }
-
-    fn process_region_name(end: &str) -> Option<String> {
-        let mut region = end.to_uppercase();
-        if let Some(pos) = region.find('-') {
-            region = region[..pos].to_string();
-        }
-        if let Some(pos) = region.find('_') {
-            region = region[..pos].to_string();
-        }
-        region = region.trim().to_string();
-        if !region.is_empty() {
-            Some(region)
-        } else {
-            None
-        }
-    }
 }
 
 #[cfg(test)]
@@ -533,8 +517,7 @@ mod tests {
     #[test]
     fn test_detect_region() {
         let mut geohack = GeoHack::new().unwrap();
-        *geohack.map_sources.p_mut().pieces_mut() =
-            vec!["region:US-NY".to_string(), "globe:mars".to_string()];
+        geohack.map_sources = MapSources::new("40_N_74_W_region:US-NY_globe:mars", "en").unwrap();
         geohack.detect_region_zoom_globe();
         assert_eq!(geohack.region_name, Some("US".to_string()));
         assert_eq!(geohack.globe, "mars");
@@ -568,6 +551,56 @@ mod tests {
         geohack.process()
     }
 
+    #[tokio::test]
+    async fn test_no_unresolved_placeholders() {
+        let cases = [
+            ("40.71_N_-74.00_E_type:city", "New York City"),
+            ("35_18_S_149_08_E_type:country", "Australia"),
+            ("64_44_N_177_30_E_type:city_source:enwiki", "Anadyr"),
+            ("10.70_S_335.25_E_globe:Venus", "Venera 8"),
+            ("9.3_S_322_W_globe:Mars", "Dawes Crater"),
+            ("13_19_N_169_9_W_globe:Moon", "Apollo 11 landing"),
+            ("46.9_S_17.5_W_globe:ganymede_dim:2988", "Dardanus Sulcus"),
+            (
+                "38.89767_N_-77.03655_E_type:landmark_region:US",
+                "The White House",
+            ),
+            (
+                "51_30_28_N_0_07_41_W_type:city(7000000)_region:GB-LND",
+                "London",
+            ),
+            ("61_10_N_23_52_E_type:city(14000)_region:FI-LS", "Akaa"),
+            ("46_10_N_8_7_E_type:city(127)_region:CH-VS", "Zwischbergen"),
+            ("36_51_S_174_47_E_type:city_region:NZ", "Auckland"),
+        ];
+        for (params, title) in cases {
+            let query = QueryParameters::new_for_test(params, Some(title));
+            let html = run_geohack(query).await.unwrap();
+            let lint = TemplateLint::new(&html);
+            assert!(lint.is_empty(), "{params}: {:?}", lint.sections());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lint_template() {
+        let query = QueryParameters::new_for_test("40.71_N_-74.00_E_type:city", None);
+        let templates = Templates::default();
+        templates.seed_test_cases().await.unwrap();
+        let mut geohack = GeoHack::new().unwrap();
+        geohack.init_from_query(query.clone()).unwrap();
+        let template_content = templates.load("en", "", &query, false).await.unwrap();
+        geohack.set_page_content(&template_content);
+        let lint = geohack.lint_template().unwrap();
+        assert!(lint.is_empty(), "{:?}", lint.sections());
+
+        geohack.set_page_content(r#"<div id="GEOTEMPLATE-FR">{nosuchplaceholder}</div>"#);
+        let lint_broken = geohack.lint_template().unwrap();
+        assert_eq!(
+            lint_broken.sections()["GEOTEMPLATE-FR"].get("nosuchplaceholder"),
+            Some(&1)
+        );
+    }
+
     #[tokio::test]
     async fn test_1() {
         // geohack.php?params=40.71_N_-74.00_E_type:city&amp;title=New+York+City
@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Why latitude, longitude could not be converted to grid coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridError {
+    /// Latitude not in [-90, 90] or longitude not in [-180, 180]
+    InvalidCoordinates {
+        latitude: f64,
+        longitude: f64,
+    },
+    /// UTM covers latitudes -80 to 84, UPS the rest
+    OutsideUtm(f64),
+    OutsideUps(f64),
+    /// A UTM zone needs a number from 1 to 60 and a latitude band letter, e.g. "32U"
+    InvalidZone(String),
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCoordinates {
+                latitude,
+                longitude,
+            } => write!(f, "Invalid coordinates {latitude}, {longitude}"),
+            Self::OutsideUtm(latitude) => {
+                write!(
+                    f,
+                    "Latitude {latitude} is outside the UTM range (-80 to 84)"
+                )
+            }
+            Self::OutsideUps(latitude) => write!(
+                f,
+                "Latitude {latitude} is outside the UPS range (south of -80 or north of 84)"
+            ),
+            Self::InvalidZone(zone) => write!(f, "Invalid UTM zone \"{zone}\""),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
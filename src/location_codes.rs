@@ -0,0 +1,152 @@
+use crate::geo_param::GeoParam;
+use crate::insert_map;
+use std::collections::HashMap;
+
+const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const GEOHASH_LENGTH: usize = 10;
+
+const PLUSCODE_ALPHABET: &[u8] = b"23456789CFGHJMPQRVWX";
+/// Number of steps per degree for a 10-digit Plus Code (1/8000 degree, about 14 m)
+const PLUSCODE_STEPS_PER_DEGREE: f64 = 8000.0;
+/// Place values of the five digit pairs, in steps
+const PLUSCODE_PLACE_VALUES: [i64; 5] = [160_000, 8_000, 400, 20, 1];
+
+/// Geocoding strings used by other services, derived from the position
+#[derive(Debug, Clone, Default)]
+pub struct LocationCodes {
+    geohash: String,
+    pluscode: String,
+    maidenhead: String,
+}
+
+impl LocationCodes {
+    pub fn new(p: &GeoParam) -> Self {
+        let lat = p.latdeg().clamp(-90.0, 90.0);
+        /* longitude in [-180,180) */
+        let lon = (p.londeg() + 180.0).rem_euclid(360.0) - 180.0;
+        Self {
+            geohash: Self::geohash(lat, lon, GEOHASH_LENGTH),
+            pluscode: Self::pluscode(lat, lon),
+            maidenhead: Self::maidenhead(lat, lon),
+        }
+    }
+
+    /// Geohash, see https://en.wikipedia.org/wiki/Geohash
+    fn geohash(lat: f64, lon: f64, length: usize) -> String {
+        let mut lat_range = (-90.0, 90.0);
+        let mut lon_range = (-180.0, 180.0);
+        let mut ret = String::with_capacity(length);
+        let mut even_bit = true;
+        while ret.len() < length {
+            let mut index = 0;
+            for _ in 0..5 {
+                let (range, value) = if even_bit {
+                    (&mut lon_range, lon)
+                } else {
+                    (&mut lat_range, lat)
+                };
+                let mid = (range.0 + range.1) / 2.0;
+                index <<= 1;
+                if value >= mid {
+                    index |= 1;
+                    range.0 = mid;
+                } else {
+                    range.1 = mid;
+                }
+                even_bit = !even_bit;
+            }
+            ret.push(GEOHASH_ALPHABET[index] as char);
+        }
+        ret
+    }
+
+    /// Open Location Code ("Plus Code") with 10 digits, see https://github.com/google/open-location-code
+    fn pluscode(lat: f64, lon: f64) -> String {
+        let max_lat = (180.0 * PLUSCODE_STEPS_PER_DEGREE) as i64 - 1;
+        let lat_steps = (((lat + 90.0) * PLUSCODE_STEPS_PER_DEGREE).floor() as i64).min(max_lat);
+        let lon_steps = (((lon + 180.0) * PLUSCODE_STEPS_PER_DEGREE).floor() as i64)
+            .rem_euclid((360.0 * PLUSCODE_STEPS_PER_DEGREE) as i64);
+
+        let mut ret = String::with_capacity(11);
+        for place_value in PLUSCODE_PLACE_VALUES {
+            if ret.len() == 8 {
+                ret.push('+');
+            }
+            ret.push(PLUSCODE_ALPHABET[(lat_steps / place_value % 20) as usize] as char);
+            ret.push(PLUSCODE_ALPHABET[(lon_steps / place_value % 20) as usize] as char);
+        }
+        ret
+    }
+
+    /// Maidenhead locator with 6 characters (subsquare), as used in amateur radio
+    fn maidenhead(lat: f64, lon: f64) -> String {
+        /* keep the north pole and the antimeridian in the last square */
+        let lat = (lat + 90.0).min(179.999_999);
+        let lon = (lon + 180.0).min(359.999_999);
+        let letter = |base: u8, value: f64| (base + value as u8) as char;
+        [
+            letter(b'A', lon / 20.0),
+            letter(b'A', lat / 10.0),
+            letter(b'0', (lon % 20.0) / 2.0),
+            letter(b'0', lat % 10.0),
+            letter(b'a', (lon % 2.0) * 12.0),
+            letter(b'a', (lat % 1.0) * 24.0),
+        ]
+        .iter()
+        .collect()
+    }
+
+    pub fn add_rep_map(&self, rep_map: &mut HashMap<String, String>) {
+        insert_map!(rep_map, {
+            "geohash" => &self.geohash,
+            "pluscode" => &self.pluscode,
+            "maidenhead" => &self.maidenhead,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geohash() {
+        // Example from Wikipedia
+        assert_eq!(
+            LocationCodes::geohash(57.64911, 10.40744, 11),
+            "u4pruydqqvj"
+        );
+        assert_eq!(LocationCodes::geohash(0.0, 0.0, 5), "s0000");
+        assert_eq!(LocationCodes::geohash(-90.0, -180.0, 3), "000");
+    }
+
+    #[test]
+    fn test_pluscode() {
+        // Examples from the Open Location Code test data
+        assert_eq!(LocationCodes::pluscode(47.365590, 8.524997), "8FVC9G8F+6X");
+        assert_eq!(LocationCodes::pluscode(90.0, 1.0), "CFX3X2X2+X2");
+        assert_eq!(LocationCodes::pluscode(-90.0, -180.0), "22222222+22");
+    }
+
+    #[test]
+    fn test_maidenhead() {
+        assert_eq!(LocationCodes::maidenhead(48.14666, 11.60833), "JN58td");
+        assert_eq!(LocationCodes::maidenhead(38.92, -77.065), "FM18lw");
+        assert_eq!(LocationCodes::maidenhead(-90.0, -180.0), "AA00aa");
+        assert_eq!(LocationCodes::maidenhead(90.0, 180.0), "RR99xx");
+    }
+
+    #[test]
+    fn test_location_codes_rep_map() {
+        let geo = GeoParam::new("47.365590_N_351.475003_W").unwrap();
+        let lc = LocationCodes::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        lc.add_rep_map(&mut rep_map);
+
+        assert_eq!(rep_map.get("pluscode").unwrap(), "8FVC9G8F+6X");
+        assert_eq!(rep_map.get("geohash").unwrap().len(), GEOHASH_LENGTH);
+        assert!(rep_map.get("geohash").unwrap().starts_with("u0qj"));
+        assert_eq!(rep_map.get("maidenhead").unwrap(), "JN47gi");
+    }
+}
@@ -38,20 +38,30 @@
     // clippy::wildcard_dependencies,
     clippy::wildcard_imports
 )]
+pub mod api;
+pub mod bounding_box;
 pub mod coordinate_group;
+pub mod datum;
+pub mod geo_attributes;
 pub mod geo_param;
+pub mod geo_param_error;
 pub mod geohack;
+pub mod grid_error;
+pub mod location_codes;
 #[macro_use]
 pub mod macros;
 pub mod map_sources;
 pub mod min_sec_result;
 pub mod misc_map_source_values;
+pub mod projection;
 pub mod query_parameters;
 pub mod regex_patterns;
 pub mod server;
+pub mod template_lint;
 pub mod templates;
 pub mod transverse_mercator_forms;
 pub mod traverse_mercator;
+pub mod viewing_direction;
 
 use anyhow::Result;
 use std::env;
@@ -28,57 +28,93 @@
  *  along with this program; if not, write to the Free Software
  *  Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
  */
+use crate::bounding_box::BoundingBox;
 use crate::coordinate_group::CoordinateGroup;
+use crate::geo_attributes::{GeoAttributes, LocationType};
 use crate::geo_param::GeoParam;
+use crate::location_codes::LocationCodes;
 use crate::misc_map_source_values::MiscMapSourceValues;
+use crate::template_lint::TemplateLint;
 use crate::transverse_mercator_forms::TransverseMercatorForms;
+use crate::viewing_direction::ViewingDirection;
 use aho_corasick::AhoCorasick;
 use anyhow::Result;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-
-/// Default scales for different location types
-static DEFAULT_SCALES: Lazy<HashMap<&'static str, i32>> = Lazy::new(|| {
-    HashMap::from([
-        ("country", 10_000_000),    // 10 mill
-        ("satellite", 10_000_000),  // 10 mill
-        ("state", 3_000_000),       // 3 mill
-        ("adm1st", 1_000_000),      // 1 mill
-        ("adm2nd", 300_000),        // 300 thousand
-        ("adm3rd", 100_000),        // 100 thousand
-        ("city", 100_000),          // 100 thousand
-        ("isle", 100_000),          // 100 thousand
-        ("mountain", 100_000),      // 100 thousand
-        ("river", 100_000),         // 100 thousand
-        ("waterbody", 100_000),     // 100 thousand
-        ("event", 50_000),          // 50 thousand
-        ("forest", 50_000),         // 50 thousand
-        ("glacier", 50_000),        // 50 thousand
-        ("airport", 30_000),        // 30 thousand
-        ("railwaystation", 10_000), // 10 thousand
-        ("edu", 10_000),            // 10 thousand
-        ("pass", 10_000),           // 10 thousand
-        ("camera", 10_000),         // 10 thousand
-        ("landmark", 10_000),       // 10 thousand
-    ])
+use std::env;
+
+/// Assumed size of the map viewport, 10 cm by 10 cm
+pub const DEFAULT_VIEWPORT_M: f64 = 0.1;
+
+/// Viewport size in metres, from `GEOHACK_VIEWPORT_CM` (in centimetres) if set.
+/// A larger viewport shows the same object at a larger scale.
+static VIEWPORT_M: Lazy<f64> = Lazy::new(|| {
+    env::var("GEOHACK_VIEWPORT_CM")
+        .ok()
+        .and_then(|cm| cm.trim().parse::<f64>().ok())
+        .filter(|cm| cm.is_finite() && *cm > 0.0)
+        .map_or(DEFAULT_VIEWPORT_M, |cm| cm / 100.0)
 });
 
-#[derive(Debug, Clone, Default)]
+/// Scales by the argument of a type, e.g. `type:city(7000000)` (minimum argument, scale).
+/// Arguments below the last threshold use `TYPE_ARG_MIN_SCALE`.
+const CITY_POPULATION_SCALES: &[(f64, i32)] = &[
+    (5_000_000.0, 1_000_000), // megacity, regional view
+    (1_000_000.0, 300_000),
+    (250_000.0, 200_000),
+    (50_000.0, 100_000),
+    (10_000.0, 50_000),
+    (1_000.0, 25_000),
+];
+/// Elevation in metres, e.g. `type:mountain(8848)`
+const MOUNTAIN_ELEVATION_SCALES: &[(f64, i32)] = &[
+    (5_000.0, 300_000),
+    (3_000.0, 200_000),
+    (1_500.0, 100_000),
+    (500.0, 50_000),
+    (100.0, 25_000),
+];
+/// Area in km², e.g. `type:waterbody(2500)` or `type:isle(2500)`
+const AREA_SCALES: &[(f64, i32)] = &[
+    (100_000.0, 10_000_000),
+    (10_000.0, 3_000_000),
+    (1_000.0, 1_000_000),
+    (100.0, 300_000),
+    (10.0, 100_000),
+    (1.0, 50_000),
+    (0.1, 25_000),
+];
+/// Villages, hills and ponds
+const TYPE_ARG_MIN_SCALE: i32 = 10_000;
+
+#[derive(Debug, Clone)]
 pub struct MapSources {
     p: GeoParam,
-    mapsources: String,
+    viewport_m: f64,
     thetext: String,
     params: Option<String>,
     language: String,
 }
 
+impl Default for MapSources {
+    fn default() -> Self {
+        MapSources {
+            p: GeoParam::default(),
+            viewport_m: *VIEWPORT_M, /* never 0, the scale is divided by it */
+            thetext: String::new(),
+            params: None,
+            language: String::new(),
+        }
+    }
+}
+
 impl MapSources {
     pub fn new(params: &str, language: &str) -> Result<Self> {
         let p = GeoParam::new(params)?;
 
         Ok(MapSources {
             p,
-            mapsources: "Map sources".to_string(),
+            viewport_m: *VIEWPORT_M,
             thetext: String::new(),
             params: Some(params.to_string()),
             language: language.to_string(),
@@ -89,34 +125,49 @@ impl MapSources {
         &self.p
     }
 
-    pub const fn p_mut(&mut self) -> &mut GeoParam {
-        &mut self.p
-    }
-
     pub fn set_thetext(&mut self, thetext: String) {
         self.thetext = thetext;
     }
 
-    pub fn build_output(&mut self, r_pagename: &str, r_title: &str) -> Result<String> {
-        let mut attr = self.p.get_attr();
-        Self::scale_dim(&mut attr);
-        Self::scale_zoom(&mut attr);
-        self.default_scale(&mut attr);
+    pub fn build_output(&self, r_pagename: &str, r_title: &str) -> Result<String> {
+        let attr = self.get_attr();
+        let rep_map = Self::escape_rep_map(self.build_rep_map(r_pagename, r_title, attr));
+        self.replace_in_page(&rep_map)
+    }
 
-        let tmf = TransverseMercatorForms::new(&self.p);
-        let cg = CoordinateGroup::new(&self.p);
-        let region = self.get_region(&attr);
-        let misc = MiscMapSourceValues::new(r_pagename, r_title, &region, attr);
+    /// List the placeholders in the template that remain after replacing all known ones
+    pub fn lint(&self, r_pagename: &str, r_title: &str) -> Result<TemplateLint> {
+        let attr = self.get_attr();
+        let rep_map = Self::escape_rep_map(self.build_rep_map(r_pagename, r_title, attr));
+        Ok(TemplateLint::new(&self.replace_in_page(&rep_map)?))
+    }
 
-        self.replace_in_page(tmf, cg, misc)
+    /// Get the attributes, with the scale derived from `dim:`, `zoom:`, `type:` etc.
+    pub fn get_attr(&self) -> GeoAttributes {
+        let mut attr = self.p.attributes().clone();
+        let scale = attr
+            .scale()
+            .or_else(|| self.scale_dim(&attr))
+            .or_else(|| Self::scale_zoom(&attr))
+            .unwrap_or_else(|| f64::from(self.default_scale(&attr)));
+        attr.set_scale(scale);
+        attr
     }
 
-    fn replace_in_page(
-        &mut self,
-        tmf: TransverseMercatorForms,
-        cg: CoordinateGroup,
-        misc: MiscMapSourceValues,
-    ) -> Result<String> {
+    /// Build the map of all `{placeholder}` replacement values, unescaped
+    pub fn build_rep_map(
+        &self,
+        r_pagename: &str,
+        r_title: &str,
+        attr: GeoAttributes,
+    ) -> HashMap<String, String> {
+        let tmf = TransverseMercatorForms::new(&self.p);
+        let cg = CoordinateGroup::new(&self.p);
+        let lc = LocationCodes::new(&self.p);
+        let bbox = BoundingBox::new(&self.p);
+        let vd = ViewingDirection::new(&self.p);
+        let misc = MiscMapSourceValues::new(r_pagename, r_title, attr, self.viewport_m);
+
         let pagename_gmaps = urlencoding::encode(misc.r_pagename())
             .into_owned()
             .replace("%20", "+");
@@ -124,18 +175,31 @@ impl MapSources {
 
         let mut rep_map: HashMap<String, String> = HashMap::new();
         cg.add_rep_map(&mut rep_map);
+        lc.add_rep_map(&mut rep_map);
+        bbox.add_rep_map(&mut rep_map);
+        vd.add_rep_map(&mut rep_map);
         tmf.add_rep_map(&mut rep_map);
         misc.add_rep_map(&mut rep_map);
         rep_map.insert(
             "params".to_string(),
-            html_escape::encode_text(self.params.as_deref().unwrap_or("")).to_string(),
-        );
-        rep_map.insert(
-            "language".to_string(),
-            html_escape::encode_text(&self.language).to_string(),
+            self.params.clone().unwrap_or_default(),
         );
+        rep_map.insert("language".to_string(), self.language.clone());
         rep_map.insert("pagename_gmaps".to_string(), pagename_gmaps);
+        rep_map
+    }
 
+    /// HTML-escape the replacement values taken verbatim from the query, for the template
+    fn escape_rep_map(mut rep_map: HashMap<String, String>) -> HashMap<String, String> {
+        for key in ["params", "language"] {
+            if let Some(value) = rep_map.get_mut(key) {
+                *value = html_escape::encode_text(value).to_string();
+            }
+        }
+        rep_map
+    }
+
+    fn replace_in_page(&self, rep_map: &HashMap<String, String>) -> Result<String> {
         // Build patterns and replacements for efficient multi-pattern replacement
         // We need to handle both {key} and &#123;key&#125; (HTML-escaped) formats
         let (patterns, replacements): (Vec<_>, Vec<_>) = rep_map
@@ -155,101 +219,75 @@ impl MapSources {
         Ok(result)
     }
 
-    fn get_region(&mut self, attr: &HashMap<String, String>) -> String {
-        /*
-         *  Look up page from Wikipedia
-         *  See if we have something in
-         *  [[Wikipedia:Map sources]] or equivalent.
-         *  A subpage can be specified
-         */
-        let mut src = self.mapsources.clone();
-        let mut region = String::new();
-
-        if let Some(page) = attr.get("page") {
-            if !page.is_empty() {
-                src.push('/');
-                src.push_str(page); // subpage specified
-            }
-        } else if let Some(globe) = attr.get("globe") {
-            if !globe.is_empty() {
-                src.push('/');
-                src.push_str(globe); // subpage specified
-            }
-        } else if let Some(reg) = attr.get("region")
-            && !reg.is_empty()
-        {
-            region = format!("/{}", reg[..2.min(reg.len())].to_uppercase());
-        }
-        region
-    }
-
-    fn default_scale(&mut self, attr: &mut HashMap<String, String>) {
-        // Default scale
-        let scale_int = attr
-            .get("scale")
-            .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(0);
-
-        if scale_int > 0 {
-            return;
-        }
-
-        let default = attr
-            .get("default")
-            .and_then(|d| d.parse::<i32>().ok())
-            .filter(|&d| d > 0)
+    /// The scale to use without `scale:`, `dim:` or `zoom:`
+    fn default_scale(&self, attr: &GeoAttributes) -> i32 {
+        attr.default_scale()
+            .or_else(|| {
+                // Fit a coordinate range into the viewport
+                let bbox = BoundingBox::new(&self.p);
+                bbox.is_range()
+                    .then(|| ((bbox.extent_m() / self.viewport_m).round() as i32).max(10_000))
+            })
             .or_else(|| {
-                // Look up default scale by type
-                attr.get("type")
-                    .and_then(|t| DEFAULT_SCALES.get(t.as_str()).copied())
-                // FIXME: Scale according to city size, if available
+                // Scale according to city size etc., if available
+                let location_type = attr.location_type()?;
+                attr.type_arg()
+                    .and_then(|arg| Self::scale_for_type_arg(location_type, arg))
+                    .or_else(|| location_type.default_scale())
             })
             .unwrap_or_else(|| {
                 // No type and no default, make an assumption based on coordinate precision
-                // FIXME: scale to input precision
-                match self.p.coor().len() {
-                    8 => 10_000,  // 10 thousand
-                    6 => 100_000, // 100 thousand
-                    _ => 300_000, // 300 thousand
-                }
-            });
+                Self::scale_for_precision(self.p.precision_m())
+            })
+    }
+
+    /// Scale from the argument of a type, e.g. the population of a city.
+    /// Returns `None` for types without a meaningful argument, or unparsable arguments.
+    fn scale_for_type_arg(location_type: &LocationType, arg: &str) -> Option<i32> {
+        let thresholds = match location_type {
+            LocationType::City => CITY_POPULATION_SCALES,
+            LocationType::Mountain => MOUNTAIN_ELEVATION_SCALES,
+            LocationType::Waterbody | LocationType::Isle => AREA_SCALES,
+            _ => return None,
+        };
+        // Allow thousands separators, e.g. "8,804,190" or "8 804 190"
+        let value = arg
+            .chars()
+            .filter(|c| !matches!(c, ',' | ' ' | '\u{a0}' | '_'))
+            .collect::<String>()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| *v > 0.0)?;
+        Some(
+            thresholds
+                .iter()
+                .find(|(threshold, _)| value >= *threshold)
+                .map(|(_, scale)| *scale)
+                .unwrap_or(TYPE_ARG_MIN_SCALE),
+        )
+    }
 
-        attr.insert("scale".to_string(), default.to_string());
+    /// A map scale that shows about 100 times the uncertainty of the input,
+    /// e.g. 1:1,000,000 for 0.1°, rounded to one significant digit
+    fn scale_for_precision(precision_m: f64) -> i32 {
+        let scale = (precision_m * 100.0).clamp(10_000.0, 10_000_000.0);
+        let magnitude = 10_f64.powi(scale.log10().floor() as i32);
+        ((scale / magnitude).round() * magnitude) as i32
     }
 
     fn quote_html(s: &str) -> String {
         s.replace('{', "&#123;").replace('}', "&#125;")
     }
 
-    fn scale_dim(attr: &mut HashMap<String, String>) {
-        //   dim: to scale: convertion
-        if !attr.contains_key("scale") && attr.contains_key("dim") {
-            // dia (m) [ (in per m) * (pixels per in) * screen size ]
-            // Assume viewport size is 10 cm by 10 cm
-            // FIXME document numbers
-            // FIXME better convertion
-            if let Some(dim) = attr.get("dim") {
-                let dim_value = dim
-                    .replace("km", "000")
-                    .replace("m", "")
-                    .parse::<f64>()
-                    .unwrap_or(0.0);
-                attr.insert("scale".to_string(), (dim_value / 0.1).to_string());
-            }
-        }
+    /// Scale at which an object with the `dim:` diameter fills the viewport
+    fn scale_dim(&self, attr: &GeoAttributes) -> Option<f64> {
+        attr.dim().map(|dim| dim / self.viewport_m)
     }
 
-    fn scale_zoom(attr: &mut HashMap<String, String>) {
+    fn scale_zoom(attr: &GeoAttributes) -> Option<f64> {
         // zoom: comptability for nlwiki
-        if !attr.contains_key("scale") && attr.contains_key("zoom") {
-            // Incompatible with {zoom} and {osmzoom}
-            if let Some(zoom) = attr.get("zoom")
-                && let Ok(zoom_val) = zoom.parse::<f64>()
-            {
-                let scale = 2_f64.powf(12.0 - zoom_val) * 100000.0;
-                attr.insert("scale".to_string(), scale.to_string());
-            }
-        }
+        // Incompatible with {zoom} and {osmzoom}
+        attr.zoom().map(|zoom| 2_f64.powf(12.0 - zoom) * 100000.0)
     }
 }
 
@@ -265,70 +303,174 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_output_escapes_params() {
+        let mut ms = MapSources::new("48.8584_N_2.2945_E_source:a&b<c", "en").unwrap();
+        ms.set_thetext("<a href=\"?params={params}&language={language}\">".to_string());
+        assert_eq!(
+            ms.build_output("", "").unwrap(),
+            "<a href=\"?params=48.8584_N_2.2945_E_source:a&amp;b&lt;c&language=en\">"
+        );
+    }
+
     #[test]
     fn test_map_sources_new() {
         let ms = MapSources::new("40_N_74_W_type:city", "en").unwrap();
         assert_eq!(ms.language, "en");
         assert!(ms.params.is_some());
-        assert_eq!(ms.mapsources, "Map sources");
+    }
+
+    fn get_scale(params: &str) -> f64 {
+        let ms = MapSources::new(params, "en").unwrap();
+        ms.get_attr().scale().unwrap()
     }
 
     #[test]
     fn test_scale_dim_conversion() {
-        let mut attr = HashMap::new();
-        attr.insert("dim".to_string(), "1000".to_string());
-
-        MapSources::scale_dim(&mut attr);
-
         // dim 1000m should convert to scale 10000 (1000 / 0.1)
-        assert!(attr.contains_key("scale"));
-        let scale: f64 = attr.get("scale").unwrap().parse().unwrap();
-        assert_eq!(scale, 10_000.0);
+        assert_eq!(get_scale("40_N_74_W_dim:1000"), 10_000.0);
     }
 
     #[test]
     fn test_scale_dim_with_km() {
-        let mut attr = HashMap::new();
-        attr.insert("dim".to_string(), "1km".to_string());
+        // dim 1km = 1000m should convert to scale 10000
+        assert_eq!(get_scale("40_N_74_W_dim:1km"), 10_000.0);
+    }
 
-        MapSources::scale_dim(&mut attr);
+    #[test]
+    fn test_scale_dim_with_units() {
+        // These used to parse as 1.5 m and 0 m
+        assert_eq!(get_scale("40_N_74_W_dim:1.5km"), 15_000.0);
+        assert!((get_scale("40_N_74_W_dim:2mi") - 32_186.88).abs() < 1e-6);
+        assert!((get_scale("40_N_74_W_dim:500ft") - 1_524.0).abs() < 1e-6);
+        assert!((get_scale("40_N_74_W_dim:1nmi") - 18_520.0).abs() < 1e-6);
+    }
 
-        // dim 1km = 1000m should convert to scale 10000
-        let scale: f64 = attr.get("scale").unwrap().parse().unwrap();
-        assert_eq!(scale, 10_000.0);
+    #[test]
+    fn test_scale_dim_viewport() {
+        let mut ms = MapSources::new("40_N_74_W_dim:1km", "en").unwrap();
+        ms.viewport_m = 0.2;
+        assert_eq!(ms.get_attr().scale(), Some(5_000.0));
+
+        let mut range = MapSources::new("40_N_74_W_to_42_N_73_W", "en").unwrap();
+        range.viewport_m = 0.2;
+        assert_eq!(range.get_attr().scale(), Some(1_113_200.0));
     }
 
     #[test]
-    fn test_scale_zoom_conversion() {
-        let mut attr = HashMap::new();
-        attr.insert("zoom".to_string(), "12".to_string());
+    fn test_default_viewport() {
+        let mut ms = MapSources::default();
+        assert!(ms.viewport_m > 0.0);
+        ms.p = GeoParam::new("40_N_74_W_dim:1km").unwrap();
+        let scale = ms.get_attr().scale().unwrap();
+        assert!(scale.is_finite() && scale > 0.0);
+    }
 
-        MapSources::scale_zoom(&mut attr);
+    #[test]
+    fn test_scale_zoom_conversion() {
+        assert_eq!(get_scale("40_N_74_W_zoom:12"), 100_000.0);
+        assert_eq!(get_scale("40_N_74_W_zoom:10"), 400_000.0);
+    }
 
-        assert!(attr.contains_key("scale"));
+    #[test]
+    fn test_scale_precedence() {
+        assert_eq!(get_scale("40_N_74_W_scale:5000_dim:1000_zoom:12"), 5_000.0);
+        assert_eq!(get_scale("40_N_74_W_dim:1000_zoom:12"), 10_000.0);
+        assert_eq!(get_scale("40_N_74_W_type:city_default:30000"), 30_000.0);
+        // An invalid scale does not hide the dimension
+        assert_eq!(get_scale("40_N_74_W_scale:abc_dim:1000"), 10_000.0);
     }
 
     #[test]
     fn test_default_scale_by_type() {
-        let mut ms = MapSources::new("40_N_74_W", "en").unwrap();
-        let mut attr = HashMap::new();
-        attr.insert("type".to_string(), "country".to_string());
+        assert_eq!(get_scale("40_N_74_W_type:country"), 10_000_000.0);
+    }
 
-        ms.default_scale(&mut attr);
+    #[test]
+    fn test_default_scale_by_coordinate_precision() {
+        // 8-piece coordinates should get 10000 scale
+        assert_eq!(get_scale("40_30_45_N_74_0_21_W"), 10_000.0);
+    }
+
+    #[test]
+    fn test_default_scale_for_range() {
+        // Two degrees of latitude in a 10 cm viewport
+        assert_eq!(get_scale("40_N_74_W_to_42_N_73_W_type:city"), 2_226_400.0);
+
+        // Across the antimeridian, 10 degrees of longitude at the equator
+        assert_eq!(get_scale("0.5_S_175_E_to_0.5_N_175_W"), 11_132_000.0);
 
-        let scale: i32 = attr.get("scale").unwrap().parse().unwrap();
-        assert_eq!(scale, 10_000_000);
+        assert_eq!(get_scale("40.1_N_74.1_W_to_40.1001_N_74.1001_W"), 10_000.0);
     }
 
     #[test]
-    fn test_default_scale_by_coordinate_precision() {
-        let mut ms = MapSources::new("40_30_45_N_74_0_21_W", "en").unwrap();
-        let mut attr = HashMap::new();
+    fn test_scale_for_type_arg() {
+        let cases = [
+            ("city", "37000000", Some(1_000_000)),
+            ("city", "8,804,190", Some(1_000_000)),
+            ("city", "3 645 000", Some(300_000)),
+            ("city", "1000000", Some(300_000)),
+            ("city", "300000", Some(200_000)),
+            ("city", "60000", Some(100_000)),
+            ("city", "12000", Some(50_000)),
+            ("city", "2500", Some(25_000)),
+            ("city", "150", Some(10_000)),
+            ("city", "", None),
+            ("city", "unknown", None),
+            ("city", "0", None),
+            ("mountain", "8848", Some(300_000)),
+            ("mountain", "4478", Some(200_000)),
+            ("mountain", "1800", Some(100_000)),
+            ("mountain", "900", Some(50_000)),
+            ("mountain", "250", Some(25_000)),
+            ("mountain", "40", Some(10_000)),
+            ("waterbody", "371000", Some(10_000_000)),
+            ("waterbody", "82100", Some(3_000_000)),
+            ("waterbody", "4.5", Some(50_000)),
+            ("waterbody", "0.05", Some(10_000)),
+            ("isle", "2130000", Some(10_000_000)),
+            ("isle", "536", Some(300_000)),
+            ("landmark", "1000", None),
+        ];
+        for (typename, arg, expected) in cases {
+            assert_eq!(
+                MapSources::scale_for_type_arg(&LocationType::new(typename), arg),
+                expected,
+                "{typename}({arg})"
+            );
+        }
+    }
 
-        ms.default_scale(&mut attr);
+    #[test]
+    fn test_default_scale_by_type_arg() {
+        assert_eq!(get_scale("40_N_74_W_type:city(8804190)"), 1_000_000.0);
 
-        // 8-piece coordinates should get 10000 scale
-        let scale: i32 = attr.get("scale").unwrap().parse().unwrap();
-        assert_eq!(scale, 10_000);
+        // A village opens at street level
+        assert_eq!(get_scale("52_N_10_E_type:city(450)"), 10_000.0);
+
+        // Types with an unknown argument fall back to the type default
+        assert_eq!(get_scale("52_N_10_E_type:landmark(1900)"), 10_000.0);
+
+        // An explicit scale wins
+        assert_eq!(
+            get_scale("52_N_10_E_type:city(8804190)_scale:5000"),
+            5_000.0
+        );
+    }
+
+    #[test]
+    fn test_default_scale_by_decimal_precision() {
+        let cases = [
+            ("40.7_N_74.0_W", 1_000_000),
+            ("40.71_N_74.01_W", 100_000),
+            ("40.7128_N_74.0060_W", 10_000),
+            ("40_N_74_W", 10_000_000),
+            ("40_42_N_74_0_W", 200_000),
+            ("40.7;-74.0", 1_000_000),
+            ("mgrs:18SUJ2306", 100_000),
+        ];
+        for (params, expected) in cases {
+            assert_eq!(get_scale(params), f64::from(expected), "{params}");
+        }
     }
 }
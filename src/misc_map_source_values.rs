@@ -1,3 +1,4 @@
+use crate::geo_attributes::GeoAttributes;
 use std::collections::HashMap;
 
 /// Multimap scale thresholds (threshold, result)
@@ -16,6 +17,10 @@ const MMSCALE_THRESHOLDS: &[(f64, i32)] = &[
     (7_000.0, 10_000),
 ];
 
+/// Horizontal field of view of the camera in 3D viewers such as Google Earth, in degrees
+const CAMERA_FIELD_OF_VIEW: f64 = 60.0;
+const METRES_PER_FOOT: f64 = 0.3048;
+
 #[derive(Debug, Clone, Default)]
 pub struct MiscMapSourceValues {
     r_pagename: String,
@@ -24,21 +29,24 @@ pub struct MiscMapSourceValues {
     zoom: i32,
     osmzoom: i32,
     altitude: i32,
+    elevation: Option<f64>,
+    lookat_range: f64,
     span: f64,
     mmscale: i32,
     region: String,
-    attr: HashMap<String, String>,
+    attr: GeoAttributes,
 }
 
 impl MiscMapSourceValues {
-    /// Create a new MiscMapSourceValues with computed derived values
-    pub fn new(
-        r_pagename: &str,
-        r_title: &str,
-        region: &str,
-        attr: HashMap<String, String>,
-    ) -> Self {
+    /// Create a new MiscMapSourceValues with computed derived values.
+    /// `viewport_m` is the size of the map viewport the scale was chosen for.
+    pub fn new(r_pagename: &str, r_title: &str, attr: GeoAttributes, viewport_m: f64) -> Self {
         let scale_float = Self::get_scale_float(&attr);
+        // Country for the localized services, independent of `page:` and `globe:`
+        let region = attr
+            .region()
+            .map(|region| format!("/{}", region.country()))
+            .unwrap_or_default();
         Self {
             r_pagename: r_pagename.to_string(),
             r_title: r_title.to_string(),
@@ -46,17 +54,17 @@ impl MiscMapSourceValues {
             zoom: Self::get_zoom(scale_float),
             osmzoom: Self::get_osmzoom(scale_float),
             altitude: Self::get_altitude(scale_float),
+            elevation: attr.elevation(),
+            lookat_range: Self::get_lookat_range(scale_float, viewport_m),
             span: Self::get_scale_float_span(scale_float),
             mmscale: Self::get_mmscale(scale_float),
-            region: region.to_string(),
+            region,
             attr,
         }
     }
 
-    pub fn get_scale_float(attr: &HashMap<String, String>) -> f64 {
-        attr.get("scale")
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(300000.0)
+    pub fn get_scale_float(attr: &GeoAttributes) -> f64 {
+        attr.scale().unwrap_or(300000.0)
     }
 
     fn get_mmscale(scale_float: f64) -> i32 {
@@ -108,6 +116,20 @@ impl MiscMapSourceValues {
         ((scale_float * 143.0 / 1000000.0) as i32).max(1)
     }
 
+    fn get_lookat_range(scale_float: f64, viewport_m: f64) -> f64 {
+        /*
+         *  Distance from which a camera sees the same
+         *  width of ground as the map viewport,
+         *  e.g. for a KML <LookAt> <range>
+         */
+        scale_float * viewport_m / (2.0 * (CAMERA_FIELD_OF_VIEW / 2.0).to_radians().tan())
+    }
+
+    /// Altitude of a camera looking straight down from `lookat_range` above the ground, in metres
+    pub fn camera_altitude(&self) -> f64 {
+        self.elevation.unwrap_or(0.0) + self.lookat_range
+    }
+
     fn get_scale_float_span(scale_float: f64) -> f64 {
         /*
          * Tiger and Google uses a span
@@ -125,6 +147,10 @@ impl MiscMapSourceValues {
         self.altitude
     }
 
+    pub const fn lookat_range(&self) -> f64 {
+        self.lookat_range
+    }
+
     pub const fn span(&self) -> f64 {
         self.span
     }
@@ -148,29 +174,28 @@ impl MiscMapSourceValues {
     pub fn region_string(&self) -> String {
         self.attr
             .get("region")
-            .map(|r| {
-                if r.len() >= 4 {
-                    r[4..r.len().min(12)].to_uppercase()
-                } else {
-                    String::new()
-                }
-            })
+            .and_then(|r| r.get(4..r.len().min(12)))
+            .map(str::to_uppercase)
             .unwrap_or_default()
     }
 
     pub fn add_rep_map(&self, rep_map: &mut HashMap<String, String>) {
-        let empty = String::new();
+        let round = |value: f64| ((value * 10.0).round() / 10.0).to_string();
         insert_map!(rep_map, {
             "scale" => self.scale_float,
             "mmscale" => self.mmscale,
             "altitude" => self.altitude,
+            "elevation_m" => self.elevation.map(round).unwrap_or_default(),
+            "elevation_ft" => self.elevation.map(|e| round(e / METRES_PER_FOOT)).unwrap_or_default(),
+            "lookat_range" => self.lookat_range.round(),
+            "camera_altitude" => self.camera_altitude().round(),
             "zoom" => self.zoom,
             "osmzoom" => self.osmzoom,
             "span" => self.span,
-            "type" => self.attr.get("type").unwrap_or(&empty),
-            "region" => self.attr.get("region").unwrap_or(&empty),
-            "globe" => self.attr.get("globe").unwrap_or(&empty),
-            "page" => self.attr.get("page").unwrap_or(&empty),
+            "type" => self.attr.get("type").unwrap_or_default(),
+            "region" => self.attr.get("region").unwrap_or_default(),
+            "globe" => self.attr.get("globe").unwrap_or_default(),
+            "page" => self.attr.get("page").unwrap_or_default(),
             "pagename" => &self.r_pagename,
             "title" => &self.r_title,
             "pagenamee" => urlencoding::encode(&self.r_pagename),
@@ -184,14 +209,18 @@ impl MiscMapSourceValues {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::map_sources::DEFAULT_VIEWPORT_M;
+
+    fn attributes(params: &str) -> GeoAttributes {
+        let pieces: Vec<String> = params.split('_').map(|s| s.to_string()).collect();
+        GeoAttributes::new(&pieces)
+    }
 
     #[test]
     fn test_region_string_with_long_region() {
-        let mut attr = HashMap::new();
         // "US-NY-NYC" has length 9, so characters 4..9 = "Y-NYC"
-        attr.insert("region".to_string(), "US-NY-NYC".to_string());
-
-        let msv = MiscMapSourceValues::new("", "", "", attr);
+        let msv =
+            MiscMapSourceValues::new("", "", attributes("region:US-NY-NYC"), DEFAULT_VIEWPORT_M);
 
         // Should extract characters from index 4 onwards (Y-NYC) and uppercase
         assert_eq!(msv.region_string(), "Y-NYC");
@@ -199,10 +228,7 @@ mod tests {
 
     #[test]
     fn test_region_string_with_short_region() {
-        let mut attr = HashMap::new();
-        attr.insert("region".to_string(), "US".to_string());
-
-        let msv = MiscMapSourceValues::new("", "", "", attr);
+        let msv = MiscMapSourceValues::new("", "", attributes("region:US"), DEFAULT_VIEWPORT_M);
 
         // Region too short (< 4 chars), should return empty
         assert_eq!(msv.region_string(), "");
@@ -218,12 +244,8 @@ mod tests {
 
     #[test]
     fn test_add_rep_map() {
-        let mut attr = HashMap::new();
-        attr.insert("type".to_string(), "city".to_string());
-        attr.insert("region".to_string(), "US-NY".to_string());
-        attr.insert("scale".to_string(), "100000".to_string());
-
-        let msv = MiscMapSourceValues::new("Test Page", "Test Title", "/US", attr);
+        let attr = attributes("type:city_region:US-NY_scale:100000");
+        let msv = MiscMapSourceValues::new("Test Page", "Test Title", attr, DEFAULT_VIEWPORT_M);
 
         let mut rep_map = HashMap::new();
         msv.add_rep_map(&mut rep_map);
@@ -240,6 +262,21 @@ mod tests {
         assert_eq!(rep_map.get("titlee").unwrap(), "Test%20Title");
     }
 
+    #[test]
+    fn test_geocountry_with_page_and_globe() {
+        // `page:` and `globe:` used to suppress the country
+        for params in [
+            "region:GB-LND_page:Lists",
+            "globe:earth_region:gb",
+            "region:GB",
+        ] {
+            let msv = MiscMapSourceValues::new("", "", attributes(params), DEFAULT_VIEWPORT_M);
+            assert_eq!(msv.region(), "/GB", "{params}");
+        }
+        let msv = MiscMapSourceValues::new("", "", attributes("type:city"), DEFAULT_VIEWPORT_M);
+        assert_eq!(msv.region(), "");
+    }
+
     #[test]
     fn test_add_rep_map_empty_attrs() {
         let msv = MiscMapSourceValues::default();
@@ -294,6 +331,35 @@ mod tests {
         assert_eq!(MiscMapSourceValues::get_altitude(100.0), 1); // Minimum is 1
     }
 
+    #[test]
+    fn test_elevation_and_camera() {
+        let attr = attributes("scale:100000_elevation:1000ft");
+        let msv = MiscMapSourceValues::new("", "", attr, DEFAULT_VIEWPORT_M);
+
+        // 10 km of ground in a 60° field of view
+        assert!((msv.lookat_range() - 8660.254).abs() < 1e-3);
+        assert!((msv.camera_altitude() - 8965.054).abs() < 1e-3);
+
+        let mut rep_map = HashMap::new();
+        msv.add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("elevation_m").unwrap(), "304.8");
+        assert_eq!(rep_map.get("elevation_ft").unwrap(), "1000");
+        assert_eq!(rep_map.get("lookat_range").unwrap(), "8660");
+        assert_eq!(rep_map.get("camera_altitude").unwrap(), "8965");
+        // The MSN scale hack is unchanged
+        assert_eq!(rep_map.get("altitude").unwrap(), "14");
+    }
+
+    #[test]
+    fn test_no_elevation() {
+        let msv = MiscMapSourceValues::new("", "", attributes("scale:100000"), 0.2);
+        let mut rep_map = HashMap::new();
+        msv.add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("elevation_m").unwrap(), "");
+        assert_eq!(rep_map.get("elevation_ft").unwrap(), "");
+        assert_eq!(rep_map.get("camera_altitude").unwrap(), "17321");
+    }
+
     #[test]
     fn test_get_scale_float_span() {
         assert_eq!(MiscMapSourceValues::get_scale_float_span(1_000_000.0), 1.0);
@@ -303,17 +369,15 @@ mod tests {
 
     #[test]
     fn test_get_scale_float() {
-        let mut attr = HashMap::new();
-        attr.insert("scale".to_string(), "500000".to_string());
+        let attr = attributes("scale:500000");
         assert_eq!(MiscMapSourceValues::get_scale_float(&attr), 500_000.0);
 
         // Default when no scale
-        let empty_attr = HashMap::new();
+        let empty_attr = GeoAttributes::default();
         assert_eq!(MiscMapSourceValues::get_scale_float(&empty_attr), 300_000.0);
 
         // Invalid scale value
-        let mut invalid_attr = HashMap::new();
-        invalid_attr.insert("scale".to_string(), "invalid".to_string());
+        let invalid_attr = attributes("scale:invalid");
         assert_eq!(
             MiscMapSourceValues::get_scale_float(&invalid_attr),
             300_000.0
@@ -322,10 +386,8 @@ mod tests {
 
     #[test]
     fn test_new_computes_derived_values() {
-        let mut attr = HashMap::new();
-        attr.insert("scale".to_string(), "1000000".to_string());
-
-        let msv = MiscMapSourceValues::new("Test Page", "Test Title", "/US", attr);
+        let attr = attributes("scale:1000000_region:US");
+        let msv = MiscMapSourceValues::new("Test Page", "Test Title", attr, DEFAULT_VIEWPORT_M);
 
         assert_eq!(msv.scale_float(), 1_000_000.0);
         assert_eq!(msv.altitude(), 143); // 1000000 * 143 / 1000000
@@ -0,0 +1,930 @@
+/**
+ *  Map projections, and the registry of national grids
+ *
+ *  A grid is a datum, a projection and the area it is used in, declared in `GRIDS`.
+ *  Every registered grid is exposed as {<name>easting}, {<name>northing} and {<name>epsg}.
+ *
+ *  See also:
+ *  IOGP Guidance Note 7-2, Coordinate Conversions and Transformations including Formulas
+ *  https://epsg.io/ for the parameters and areas of use
+ */
+use crate::datum::{Datum, Ellipsoid};
+use crate::traverse_mercator::TransverseMercator;
+use std::f64::consts::PI;
+
+/// A map projection, with angles in decimal degrees and offsets in metres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    TransverseMercator {
+        latitude_origin: f64,
+        longitude_origin: f64,
+        scale: f64,
+        false_easting: f64,
+        false_northing: f64,
+    },
+    /// Lambert Conformal Conic with two standard parallels (EPSG method 9802)
+    LambertConformalConic {
+        latitude_origin: f64,
+        longitude_origin: f64,
+        standard_parallels: (f64, f64),
+        false_easting: f64,
+        false_northing: f64,
+    },
+    /// Oblique stereographic, double projection via the conformal sphere (EPSG method 9809)
+    ObliqueStereographic {
+        latitude_origin: f64,
+        longitude_origin: f64,
+        scale: f64,
+        false_easting: f64,
+        false_northing: f64,
+    },
+    /// Polar stereographic with the origin at the pole (EPSG method 9810)
+    PolarStereographic {
+        north: bool,
+        longitude_origin: f64,
+        scale: f64,
+        false_easting: f64,
+        false_northing: f64,
+    },
+    /// Swiss oblique conformal cylindrical projection, according to "Formulas and constants
+    /// for the calculation of the Swiss conformal cylindrical projection", swisstopo, chapter 3
+    SwissObliqueCylindrical {
+        latitude_origin: f64,
+        longitude_origin: f64,
+        false_easting: f64,
+        false_northing: f64,
+    },
+}
+
+impl Projection {
+    /// Universal Polar Stereographic for the northern or southern polar cap
+    pub const fn ups(north: bool) -> Self {
+        Self::PolarStereographic {
+            north,
+            longitude_origin: 0.0,
+            scale: 0.994,
+            false_easting: 2000000.0,
+            false_northing: 2000000.0,
+        }
+    }
+
+    /// Convert latitude, longitude in decimal degrees on `ellipsoid` to Easting and Northing
+    pub fn project(
+        &self,
+        ellipsoid: &Ellipsoid,
+        latitude: f64,
+        longitude: f64,
+    ) -> Option<(f64, f64)> {
+        match *self {
+            Self::TransverseMercator {
+                latitude_origin,
+                longitude_origin,
+                scale,
+                false_easting,
+                false_northing,
+            } => {
+                let mut tm =
+                    TransverseMercator::new(ellipsoid, scale, false_easting, false_northing);
+                tm.lat_lon_origin_to_tm(latitude, longitude, latitude_origin, longitude_origin)
+                    .ok()
+                    .map(|()| (tm.easting(), tm.northing()))
+            }
+            Self::LambertConformalConic {
+                latitude_origin,
+                longitude_origin,
+                standard_parallels,
+                false_easting,
+                false_northing,
+            } => Some(Self::lambert_conformal_conic(
+                ellipsoid,
+                latitude.to_radians(),
+                (longitude - longitude_origin).to_radians(),
+                latitude_origin.to_radians(),
+                (
+                    standard_parallels.0.to_radians(),
+                    standard_parallels.1.to_radians(),
+                ),
+                (false_easting, false_northing),
+            )),
+            Self::ObliqueStereographic {
+                latitude_origin,
+                longitude_origin,
+                scale,
+                false_easting,
+                false_northing,
+            } => Some(Self::oblique_stereographic(
+                ellipsoid,
+                latitude.to_radians(),
+                (longitude - longitude_origin).to_radians(),
+                latitude_origin.to_radians(),
+                scale,
+                (false_easting, false_northing),
+            )),
+            Self::PolarStereographic {
+                north,
+                longitude_origin,
+                scale,
+                false_easting,
+                false_northing,
+            } => {
+                if north != (latitude > 0.0) {
+                    return None;
+                }
+                /* the formula for the north pole, mirrored for the south pole */
+                let lat_rad = latitude.abs().to_radians();
+                let lon_rad = (longitude - longitude_origin).to_radians();
+                let e = ellipsoid.eccentricity().sqrt();
+                let t = (PI / 4.0 - lat_rad / 2.0).tan()
+                    / ((1.0 - e * lat_rad.sin()) / (1.0 + e * lat_rad.sin())).powf(e / 2.0);
+                let rho = 2.0 * ellipsoid.radius() * scale * t
+                    / ((1.0 + e).powf(1.0 + e) * (1.0 - e).powf(1.0 - e)).sqrt();
+                let northing = if north {
+                    false_northing - rho * lon_rad.cos()
+                } else {
+                    false_northing + rho * lon_rad.cos()
+                };
+                Some((false_easting + rho * lon_rad.sin(), northing))
+            }
+            Self::SwissObliqueCylindrical {
+                latitude_origin,
+                longitude_origin,
+                false_easting,
+                false_northing,
+            } => Some(Self::swiss_oblique_cylindrical(
+                ellipsoid,
+                latitude.to_radians(),
+                (longitude - longitude_origin).to_radians(),
+                latitude_origin.to_radians(),
+                (false_easting, false_northing),
+            )),
+        }
+    }
+
+    fn lambert_conformal_conic(
+        ellipsoid: &Ellipsoid,
+        lat_rad: f64,
+        lon_rad: f64,
+        lat0: f64,
+        (lat1, lat2): (f64, f64),
+        (false_easting, false_northing): (f64, f64),
+    ) -> (f64, f64) {
+        let e2 = ellipsoid.eccentricity();
+        let e = e2.sqrt();
+        let m = |lat: f64| lat.cos() / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+        let t = |lat: f64| {
+            (PI / 4.0 - lat / 2.0).tan()
+                / ((1.0 - e * lat.sin()) / (1.0 + e * lat.sin())).powf(e / 2.0)
+        };
+
+        /* cone constant; a single standard parallel if both are the same */
+        let n = if (lat1 - lat2).abs() < 1e-12 {
+            lat1.sin()
+        } else {
+            (m(lat1).ln() - m(lat2).ln()) / (t(lat1).ln() - t(lat2).ln())
+        };
+        let f = m(lat1) / (n * t(lat1).powf(n));
+        let r = ellipsoid.radius() * f * t(lat_rad).powf(n);
+        let r0 = ellipsoid.radius() * f * t(lat0).powf(n);
+        let theta = n * lon_rad;
+
+        (
+            false_easting + r * theta.sin(),
+            false_northing + r0 - r * theta.cos(),
+        )
+    }
+
+    fn oblique_stereographic(
+        ellipsoid: &Ellipsoid,
+        lat_rad: f64,
+        lon_rad: f64,
+        lat0: f64,
+        scale: f64,
+        (false_easting, false_northing): (f64, f64),
+    ) -> (f64, f64) {
+        let a = ellipsoid.radius();
+        let e2 = ellipsoid.eccentricity();
+        let e = e2.sqrt();
+
+        /* radius of the conformal sphere */
+        let rho0 = a * (1.0 - e2) / (1.0 - e2 * lat0.sin().powi(2)).powf(1.5);
+        let nu0 = a / (1.0 - e2 * lat0.sin().powi(2)).sqrt();
+        let r = (rho0 * nu0).sqrt();
+        let n = (1.0 + e2 * lat0.cos().powi(4) / (1.0 - e2)).sqrt();
+
+        /* conformal latitude, with c chosen so that the origin keeps its latitude */
+        let w = |lat: f64| {
+            let sa = (1.0 + lat.sin()) / (1.0 - lat.sin());
+            let sb = (1.0 - e * lat.sin()) / (1.0 + e * lat.sin());
+            (sa * sb.powf(e)).powf(n)
+        };
+        let w1 = w(lat0);
+        let sin_chi00 = (w1 - 1.0) / (w1 + 1.0);
+        let c = (n + lat0.sin()) * (1.0 - sin_chi00) / ((n - lat0.sin()) * (1.0 + sin_chi00));
+        let w2 = c * w1;
+        let chi0 = ((w2 - 1.0) / (w2 + 1.0)).asin();
+        let w3 = c * w(lat_rad);
+        let chi = ((w3 - 1.0) / (w3 + 1.0)).asin();
+        let lambda = n * lon_rad;
+
+        /* stereographic projection of the sphere */
+        let b = 1.0 + chi.sin() * chi0.sin() + chi.cos() * chi0.cos() * lambda.cos();
+        (
+            false_easting + 2.0 * r * scale * chi.cos() * lambda.sin() / b,
+            false_northing
+                + 2.0
+                    * r
+                    * scale
+                    * (chi.sin() * chi0.cos() - chi.cos() * chi0.sin() * lambda.cos())
+                    / b,
+        )
+    }
+
+    fn swiss_oblique_cylindrical(
+        ellipsoid: &Ellipsoid,
+        lat_rad: f64,
+        lon_rad: f64,
+        lat0: f64,
+        (false_easting, false_northing): (f64, f64),
+    ) -> (f64, f64) {
+        let e2 = ellipsoid.eccentricity();
+        let e = e2.sqrt();
+        let iso = |lat: f64| {
+            /* isometric latitude, without the constant */
+            (PI / 4.0 + lat / 2.0).tan().ln()
+                - e / 2.0 * ((1.0 + e * lat.sin()) / (1.0 - e * lat.sin())).ln()
+        };
+
+        /* Ellipsoid to sphere */
+        let r = ellipsoid.radius() * (1.0 - e2).sqrt() / (1.0 - e2 * lat0.sin().powi(2));
+        let alpha = (1.0 + e2 / (1.0 - e2) * lat0.cos().powi(4)).sqrt();
+        let b0 = (lat0.sin() / alpha).asin();
+        let k = (PI / 4.0 + b0 / 2.0).tan().ln() - alpha * iso(lat0);
+
+        let s = alpha * iso(lat_rad) + k;
+        let b = 2.0 * (s.exp().atan() - PI / 4.0);
+        let l = alpha * lon_rad;
+
+        /* Sphere to pseudo-equatorial system */
+        let b_term = b0.sin() * b.tan();
+        let l_term = b0.cos() * l.cos();
+        let l_bar = (l.sin() / (b_term + l_term)).atan();
+        let b_bar = (b0.cos() * b.sin() - b0.sin() * b.cos() * l.cos()).asin();
+
+        /* Sphere to plane */
+        (
+            false_easting + r * l_bar,
+            false_northing + r / 2.0 * ((1.0 + b_bar.sin()) / (1.0 - b_bar.sin())).ln(),
+        )
+    }
+}
+
+/// Area of use of a grid, in WGS-84 decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    south: f64,
+    north: f64,
+    west: f64,
+    east: f64,
+}
+
+impl Bounds {
+    pub const fn new(south: f64, north: f64, west: f64, east: f64) -> Self {
+        Self {
+            south,
+            north,
+            west,
+            east,
+        }
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        (self.south..=self.north).contains(&latitude)
+            && (self.west..=self.east).contains(&longitude)
+    }
+}
+
+/// How the grid is offered for a point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridFamily {
+    /// A grid of its own, only limited by its bounds
+    Single,
+    /// One zone of the Gauss-Krüger grid of its country, see `Grid::gauss_krueger`
+    GaussKrueger,
+}
+
+/// A national or regional grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    name: &'static str,    /* placeholder prefix, e.g. "lv95" for {lv95easting} */
+    country: &'static str, /* ISO 3166-1 code of the country using the grid */
+    family: GridFamily,
+    epsg: u32,
+    datum: Datum,
+    projection: Projection,
+    bounds: Bounds,
+}
+
+/// The registered grids; each one becomes {<name>easting}, {<name>northing} and {<name>epsg}
+pub const GRIDS: &[Grid] = &[
+    /* fixed UTM zone 33 as used for all of Norway, e.g. by norgeskart.no */
+    Grid {
+        name: "utm33",
+        country: "NO",
+        family: GridFamily::Single,
+        epsg: 25833,
+        datum: Datum::ETRS89,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 15.0,
+            scale: 0.9996,
+            false_easting: 500000.0,
+            false_northing: 0.0,
+        },
+        bounds: Bounds::new(57.0, 81.0, -10.0, 34.0),
+    },
+    /* UK National Grid */
+    Grid {
+        name: "osgb36",
+        country: "GB",
+        family: GridFamily::Single,
+        epsg: 27700,
+        datum: Datum::OSGB36,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 49.0,
+            longitude_origin: -2.0,
+            scale: 0.9996012717,
+            false_easting: 400000.0,
+            false_northing: -100000.0,
+        },
+        bounds: Bounds::new(49.75, 61.01, -9.01, 2.01),
+    },
+    /* Swiss traditional national grid (LV03), projection centre is the old observatory of Bern */
+    Grid {
+        name: "ch1903",
+        country: "CH",
+        family: GridFamily::Single,
+        epsg: 21781,
+        datum: Datum::CH1903,
+        projection: Projection::SwissObliqueCylindrical {
+            latitude_origin: 46.952405555556,
+            longitude_origin: 7.439583333333,
+            false_easting: 600000.0,
+            false_northing: 200000.0,
+        },
+        bounds: Bounds::new(45.82, 47.81, 5.96, 10.49),
+    },
+    /* Swiss LV95 national grid */
+    Grid {
+        name: "lv95",
+        country: "CH",
+        family: GridFamily::Single,
+        epsg: 2056,
+        datum: Datum::CH1903PLUS,
+        projection: Projection::SwissObliqueCylindrical {
+            latitude_origin: 46.952405555556,
+            longitude_origin: 7.439583333333,
+            false_easting: 2600000.0,
+            false_northing: 1200000.0,
+        },
+        bounds: Bounds::new(45.82, 47.81, 5.96, 10.49),
+    },
+    /* French Lambert-93 */
+    Grid {
+        name: "lambert93",
+        country: "FR",
+        family: GridFamily::Single,
+        epsg: 2154,
+        datum: Datum::RGF93,
+        projection: Projection::LambertConformalConic {
+            latitude_origin: 46.5,
+            longitude_origin: 3.0,
+            standard_parallels: (49.0, 44.0),
+            false_easting: 700000.0,
+            false_northing: 6600000.0,
+        },
+        bounds: Bounds::new(41.15, 51.56, -9.86, 10.38),
+    },
+    /* Belgian Lambert 2008 */
+    Grid {
+        name: "lambert2008",
+        country: "BE",
+        family: GridFamily::Single,
+        epsg: 3812,
+        datum: Datum::ETRS89,
+        projection: Projection::LambertConformalConic {
+            latitude_origin: 50.797815,
+            longitude_origin: 4.359215833333,
+            standard_parallels: (49.833333333333, 51.166666666667),
+            false_easting: 649328.0,
+            false_northing: 665262.0,
+        },
+        bounds: Bounds::new(49.5, 51.51, 2.5, 6.4),
+    },
+    /* Belgian Lambert 72 */
+    Grid {
+        name: "lambert72",
+        country: "BE",
+        family: GridFamily::Single,
+        epsg: 31370,
+        datum: Datum::BD72,
+        projection: Projection::LambertConformalConic {
+            latitude_origin: 90.0,
+            longitude_origin: 4.367486666667,
+            standard_parallels: (51.166667233333, 49.8333339),
+            false_easting: 150000.013,
+            false_northing: 5400088.438,
+        },
+        bounds: Bounds::new(49.5, 51.51, 2.5, 6.4),
+    },
+    /* Dutch Rijksdriehoek (RD New), projection centre is the Onze Lieve Vrouwetoren in Amersfoort */
+    Grid {
+        name: "rd",
+        country: "NL",
+        family: GridFamily::Single,
+        epsg: 28992,
+        datum: Datum::AMERSFOORT,
+        projection: Projection::ObliqueStereographic {
+            latitude_origin: 52.156160555556,
+            longitude_origin: 5.387638888889,
+            scale: 0.9999079,
+            false_easting: 155000.0,
+            false_northing: 463000.0,
+        },
+        bounds: Bounds::new(50.75, 53.7, 3.2, 7.22),
+    },
+    /* German Gauss-Krüger zones 2 to 5, 3° wide, with the zone number in front of the Easting */
+    Grid {
+        name: "gk2",
+        country: "DE",
+        family: GridFamily::GaussKrueger,
+        epsg: 31466,
+        datum: Datum::DHDN,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 6.0,
+            scale: 1.0,
+            false_easting: 2500000.0,
+            false_northing: 0.0,
+        },
+        bounds: Bounds::new(47.27, 55.09, 5.86, 7.5),
+    },
+    Grid {
+        name: "gk3",
+        country: "DE",
+        family: GridFamily::GaussKrueger,
+        epsg: 31467,
+        datum: Datum::DHDN,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 9.0,
+            scale: 1.0,
+            false_easting: 3500000.0,
+            false_northing: 0.0,
+        },
+        bounds: Bounds::new(47.27, 55.09, 7.5, 10.5),
+    },
+    Grid {
+        name: "gk4",
+        country: "DE",
+        family: GridFamily::GaussKrueger,
+        epsg: 31468,
+        datum: Datum::DHDN,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 12.0,
+            scale: 1.0,
+            false_easting: 4500000.0,
+            false_northing: 0.0,
+        },
+        bounds: Bounds::new(47.27, 55.09, 10.5, 13.5),
+    },
+    Grid {
+        name: "gk5",
+        country: "DE",
+        family: GridFamily::GaussKrueger,
+        epsg: 31469,
+        datum: Datum::DHDN,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 15.0,
+            scale: 1.0,
+            false_easting: 5500000.0,
+            false_northing: 0.0,
+        },
+        bounds: Bounds::new(47.27, 55.09, 13.5, 15.04),
+    },
+    /* Austrian Gauss-Krüger zones M28, M31 and M34, named after their meridian east of Ferro */
+    Grid {
+        name: "gkm28",
+        country: "AT",
+        family: GridFamily::GaussKrueger,
+        epsg: 31254,
+        datum: Datum::MGI,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 10.333333333333,
+            scale: 1.0,
+            false_easting: 0.0,
+            false_northing: -5000000.0,
+        },
+        bounds: Bounds::new(46.4, 49.02, 9.53, 11.84),
+    },
+    Grid {
+        name: "gkm31",
+        country: "AT",
+        family: GridFamily::GaussKrueger,
+        epsg: 31255,
+        datum: Datum::MGI,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 13.333333333333,
+            scale: 1.0,
+            false_easting: 0.0,
+            false_northing: -5000000.0,
+        },
+        bounds: Bounds::new(46.4, 49.02, 11.83, 14.84),
+    },
+    Grid {
+        name: "gkm34",
+        country: "AT",
+        family: GridFamily::GaussKrueger,
+        epsg: 31256,
+        datum: Datum::MGI,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 16.333333333333,
+            scale: 1.0,
+            false_easting: 0.0,
+            false_northing: -5000000.0,
+        },
+        bounds: Bounds::new(46.4, 49.02, 14.83, 17.17),
+    },
+    /* Estonian L-EST97 */
+    Grid {
+        name: "lest97",
+        country: "EE",
+        family: GridFamily::Single,
+        epsg: 3301,
+        datum: Datum::ETRS89,
+        projection: Projection::LambertConformalConic {
+            latitude_origin: 57.517553930556,
+            longitude_origin: 24.0,
+            standard_parallels: (59.333333333333, 58.0),
+            false_easting: 500000.0,
+            false_northing: 6375000.0,
+        },
+        bounds: Bounds::new(57.52, 60.0, 21.74, 28.2),
+    },
+    /* Irish Grid */
+    Grid {
+        name: "irishgrid",
+        country: "IE",
+        family: GridFamily::Single,
+        epsg: 29903,
+        datum: Datum::TM75,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 53.5,
+            longitude_origin: -8.0,
+            scale: 1.000035,
+            false_easting: 200000.0,
+            false_northing: 250000.0,
+        },
+        bounds: Bounds::new(51.39, 55.43, -10.56, -5.34),
+    },
+    /* Irish Transverse Mercator */
+    Grid {
+        name: "itm",
+        country: "IE",
+        family: GridFamily::Single,
+        epsg: 2157,
+        datum: Datum::ETRS89,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 53.5,
+            longitude_origin: -8.0,
+            scale: 0.99982,
+            false_easting: 600000.0,
+            false_northing: 750000.0,
+        },
+        bounds: Bounds::new(51.39, 55.43, -10.56, -5.34),
+    },
+    /* New Zealand Transverse Mercator 2000 */
+    Grid {
+        name: "nztm",
+        country: "NZ",
+        family: GridFamily::Single,
+        epsg: 2193,
+        datum: Datum::NZGD2000,
+        projection: Projection::TransverseMercator {
+            latitude_origin: 0.0,
+            longitude_origin: 173.0,
+            scale: 0.9996,
+            false_easting: 1600000.0,
+            false_northing: 10000000.0,
+        },
+        bounds: Bounds::new(-47.33, -34.1, 166.37, 178.63),
+    },
+];
+
+impl Grid {
+    pub fn find(name: &str) -> Option<&'static Grid> {
+        GRIDS.iter().find(|grid| grid.name == name)
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub const fn epsg(&self) -> u32 {
+        self.epsg
+    }
+
+    /// The Gauss-Krüger zone for a point in `country` (DE or AT, from `region:`), with its
+    /// Easting (Rechtswert) and Northing (Hochwert). The zones of both countries extend across
+    /// their borders, so the country cannot be told from the zone bounds alone.
+    pub fn gauss_krueger(
+        country: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> Option<(&'static Grid, (f64, f64))> {
+        GRIDS
+            .iter()
+            .filter(|grid| grid.family == GridFamily::GaussKrueger && grid.country == country)
+            .find_map(|grid| Some((grid, grid.project(latitude, longitude)?)))
+    }
+
+    /// Convert WGS-84 latitude, longitude in decimal degrees to Easting and Northing,
+    /// or None outside the area of the grid
+    pub fn project(&self, latitude: f64, longitude: f64) -> Option<(f64, f64)> {
+        if !self.bounds.contains(latitude, longitude) {
+            return None;
+        }
+        let (latitude, longitude) = self.datum.from_wgs84(latitude, longitude);
+        self.projection
+            .project(self.datum.ellipsoid(), latitude, longitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str, latitude: f64, longitude: f64) -> Option<(f64, f64)> {
+        Grid::find(name).unwrap().project(latitude, longitude)
+    }
+
+    #[test]
+    fn test_grid_names() {
+        for (i, grid) in GRIDS.iter().enumerate() {
+            assert_eq!(Grid::find(grid.name()), Some(&GRIDS[i]));
+        }
+        assert!(Grid::find("nonsense").is_none());
+        assert_eq!(Grid::find("lv95").unwrap().epsg(), 2056);
+    }
+
+    #[test]
+    fn test_osgb36_control_points() {
+        // Snowdon summit trig pillar, SH 60986 54375
+        let (easting, northing) = project("osgb36", 53.068497, -4.076231).unwrap();
+        assert!((easting - 260986.0).abs() < 5.0);
+        assert!((northing - 354375.0).abs() < 5.0);
+
+        // New York
+        assert!(project("osgb36", 40.7128, -74.0060).is_none());
+    }
+
+    #[test]
+    fn test_swiss_grids() {
+        // swisstopo reference point: 46° 2' 38.87" N, 8° 43' 49.79" E => 2 700 000 / 1 100 000
+        let latitude = 46.0 + 2.0 / 60.0 + 38.87 / 3600.0;
+        let longitude = 8.0 + 43.0 / 60.0 + 49.79 / 3600.0;
+        let (easting, northing) = project("lv95", latitude, longitude).unwrap();
+        assert!((easting - 2700000.0).abs() < 0.1);
+        assert!((northing - 1100000.0).abs() < 0.1);
+
+        // LV03 only differs by the false origin
+        let (lv03_easting, lv03_northing) = project("ch1903", latitude, longitude).unwrap();
+        assert!((lv03_easting - 700000.0).abs() < 0.1);
+        assert!((lv03_northing - 100000.0).abs() < 0.1);
+
+        // Bern, compared to the swisstopo approximation
+        let (bern_easting, bern_northing) = project("ch1903", 46.9480, 7.4474).unwrap();
+        let (lat, lon) = TransverseMercator::ch1903_to_lat_lon(bern_easting, bern_northing);
+        assert!((lat - 46.9480).abs() < 1e-5);
+        assert!((lon - 7.4474).abs() < 1e-5);
+
+        // Test out of range
+        assert!(project("lv95", 50.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_lambert_conformal_conic() {
+        // EPSG Guidance Note 7-2 example, NAD27 / Texas South Central, in US survey feet
+        let us_foot = 1200.0 / 3937.0;
+        let flattening = 1.0 / 294.9786982;
+        let clarke_1866 = Ellipsoid::new(6378206.4, flattening * (2.0 - flattening));
+        let texas = Projection::LambertConformalConic {
+            latitude_origin: 27.0 + 50.0 / 60.0,
+            longitude_origin: -99.0,
+            standard_parallels: (28.0 + 23.0 / 60.0, 30.0 + 17.0 / 60.0),
+            false_easting: 2000000.0 * us_foot,
+            false_northing: 0.0,
+        };
+        let (easting, northing) = texas.project(&clarke_1866, 28.5, -96.0).unwrap();
+        assert!((easting / us_foot - 2963503.91).abs() < 0.01);
+        assert!((northing / us_foot - 254759.80).abs() < 0.01);
+
+        // EPSG Guidance Note 7-2 example for Belgian Lambert 72, on the BD72 datum.
+        // The example uses the older Belgium variant, which EPSG:31370 matches to a few cm.
+        let lambert72 = Grid::find("lambert72").unwrap().projection;
+        let latitude = 50.0 + 40.0 / 60.0 + 46.461 / 3600.0;
+        let longitude = 5.0 + 48.0 / 60.0 + 26.533 / 3600.0;
+        let (be_easting, be_northing) = lambert72
+            .project(&Ellipsoid::INTERNATIONAL_1924, latitude, longitude)
+            .unwrap();
+        assert!((be_easting - 251763.20).abs() < 0.05);
+        assert!((be_northing - 153034.13).abs() < 0.05);
+    }
+
+    /// Scale factor of a grid along the parallel through a point, from the grid distance of two
+    /// points 0.01° apart and their distance along the GRS80 parallel
+    fn scale_on_parallel(name: &str, latitude: f64, longitude: f64) -> f64 {
+        let (west_easting, west_northing) = project(name, latitude, longitude - 0.005).unwrap();
+        let (east_easting, east_northing) = project(name, latitude, longitude + 0.005).unwrap();
+        let grid_distance = (east_easting - west_easting).hypot(east_northing - west_northing);
+        let sin_lat = latitude.to_radians().sin();
+        let normal_radius = 6378137.0 / (1.0 - 0.00669438002290 * sin_lat * sin_lat).sqrt();
+        grid_distance / (normal_radius * latitude.to_radians().cos() * 0.01_f64.to_radians())
+    }
+
+    #[test]
+    fn test_lambert_ign_example() {
+        // IGN NTG_71 test data for ALG0003, NTF Lambert I on the Clarke 1880 (IGN) ellipsoid,
+        // at 0.872664626 rad N, 0.145512099 rad E of Greenwich
+        let clarke_1880_ign = Ellipsoid::new(6378249.2, 0.0068034876462998994);
+        let dms = |d: f64, m: f64, s: f64| d + m / 60.0 + s / 3600.0;
+        let lambert_1 = Projection::LambertConformalConic {
+            latitude_origin: 49.5,
+            longitude_origin: dms(2.0, 20.0, 14.025),
+            standard_parallels: (dms(48.0, 35.0, 54.682), dms(50.0, 23.0, 45.282)),
+            false_easting: 600000.0,
+            false_northing: 200000.0,
+        };
+        let latitude = 0.872664626_f64.to_degrees();
+        let longitude = 0.145512099_f64.to_degrees();
+        let (easting, northing) = lambert_1
+            .project(&clarke_1880_ign, latitude, longitude)
+            .unwrap();
+        assert!((easting - 1029705.0818).abs() < 0.01);
+        assert!((northing - 272723.8510).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_lambert_grids() {
+        // The RGF93 / Lambert-93 point of the proj4rs test_transform_null_datum test
+        // (tests/proj4js_tests.rs), checked there against cs2cs, 46° 21' 18" N, 0° 15' 36" E;
+        // the test gives it in EPSG:3857, as 28943.07106251, 5837421.86634143
+        let (easting, northing) = project("lambert93", 46.354999964, 0.260000031).unwrap();
+        assert!((easting - 489353.59).abs() < 0.01);
+        assert!((northing - 6587552.2).abs() < 0.01);
+
+        // Lambert-93 is true to scale on its standard parallels, 44° and 49° N
+        assert!((scale_on_parallel("lambert93", 44.0, 3.0) - 1.0).abs() < 1e-9);
+        assert!((scale_on_parallel("lambert93", 49.0, 3.0) - 1.0).abs() < 1e-9);
+        assert!((scale_on_parallel("lambert93", 46.5, 3.0) - 0.99905).abs() < 1e-5);
+
+        // Lambert 2008 is true to scale on 49° 50' and 51° 10' N
+        assert!((scale_on_parallel("lambert2008", 49.833333333, 4.5) - 1.0).abs() < 1e-9);
+        assert!((scale_on_parallel("lambert2008", 51.166666667, 4.5) - 1.0).abs() < 1e-9);
+
+        // Lambert 2008 is Lambert 72 plus 500 km in both directions, to about a metre,
+        // compared at the Lambert 2008 false origin
+        let (be_easting, be_northing) = project("lambert2008", 50.797815, 4.359215833).unwrap();
+        let (l72_easting, l72_northing) = project("lambert72", 50.797815, 4.359215833).unwrap();
+        assert!((be_easting - l72_easting - 500000.0).abs() < 1.0);
+        assert!((be_northing - l72_northing - 500000.0).abs() < 1.0);
+
+        // The EPSG Guidance Note 7-2 Lambert 72 example point, 50° 40' 46.461" N,
+        // 5° 48' 26.533" E on BD72, converted to WGS-84 with the EPSG:15929 parameters,
+        // and projected again through the BD72 datum shift of the grid
+        let (l72_x, l72_y) = project("lambert72", 50.679014286, 5.808673885).unwrap();
+        assert!((l72_x - 251763.20).abs() < 0.1);
+        assert!((l72_y - 153034.13).abs() < 0.1);
+
+        // Test out of range
+        assert!(project("lambert93", 51.5074, -0.1278).is_some());
+        assert!(project("lambert93", 40.7128, -74.0060).is_none());
+        assert!(project("lambert2008", 48.8530, 2.3499).is_none());
+    }
+
+    #[test]
+    fn test_lest97() {
+        // L-EST97 is true to scale on its standard parallels, 58° and 59° 20' N
+        assert!((scale_on_parallel("lest97", 58.0, 24.0) - 1.0).abs() < 1e-9);
+        assert!((scale_on_parallel("lest97", 59.333333333, 24.0) - 1.0).abs() < 1e-9);
+        // Test out of range
+        assert!(project("lest97", 56.9496, 24.1052).is_none());
+    }
+
+    #[test]
+    fn test_oblique_stereographic() {
+        // EPSG Guidance Note 7-2 example for RD New, on the Amersfoort datum
+        let rd = Grid::find("rd").unwrap().projection;
+        let (easting, northing) = rd.project(&Ellipsoid::BESSEL_1841, 53.0, 6.0).unwrap();
+        assert!((easting - 196105.283).abs() < 0.001);
+        assert!((northing - 557057.739).abs() < 0.001);
+
+        // The Onze Lieve Vrouwetoren in Amersfoort, at the origin of the grid; the datum shift
+        // is good to about a metre
+        let (tower_x, tower_y) = project("rd", 52.15517440, 5.38720621).unwrap();
+        assert!((tower_x - 155000.0).abs() < 1.0);
+        assert!((tower_y - 463000.0).abs() < 1.0);
+
+        // Test out of range
+        assert!(project("rd", 48.8530, 2.3499).is_none());
+    }
+
+    #[test]
+    fn test_gauss_krueger() {
+        // Frankfurt am Main, zone 3
+        let (grid, (rechts, hoch)) = Grid::gauss_krueger("DE", 50.1109, 8.6821).unwrap();
+        assert_eq!(grid.name(), "gk3");
+        assert!((rechts - 3477335.0).abs() < 1.0);
+        assert!((hoch - 5552791.0).abs() < 1.0);
+
+        // Vienna, zone M34
+        let (vienna, (vienna_rechts, vienna_hoch)) =
+            Grid::gauss_krueger("AT", 48.2085, 16.3731).unwrap();
+        assert_eq!(vienna.name(), "gkm34");
+        assert!((vienna_rechts - 3045.0).abs() < 1.0);
+        assert!((vienna_hoch - 341123.0).abs() < 1.0);
+
+        // Salzburg is inside both the German and the Austrian zones; the region decides
+        let salzburg = |country| Grid::gauss_krueger(country, 47.8095, 13.0550).unwrap().0;
+        assert_eq!(salzburg("AT").name(), "gkm31");
+        assert_eq!(salzburg("DE").name(), "gk4");
+
+        // Only Germany and Austria have Gauss-Krüger zones
+        assert!(Grid::gauss_krueger("CH", 47.3769, 8.5417).is_none());
+        assert!(Grid::gauss_krueger("FR", 48.5734, 7.7521).is_none());
+
+        // Test out of range
+        assert!(Grid::gauss_krueger("DE", 48.8530, 2.3499).is_none());
+        assert!(Grid::gauss_krueger("AT", 50.1109, 8.6821).is_none());
+
+        // The zones are declared as data
+        let zones: Vec<_> = GRIDS
+            .iter()
+            .filter(|zone| zone.family == GridFamily::GaussKrueger)
+            .map(|zone| zone.name())
+            .collect();
+        assert_eq!(
+            zones,
+            ["gk2", "gk3", "gk4", "gk5", "gkm28", "gkm31", "gkm34"]
+        );
+    }
+
+    #[test]
+    fn test_irish_grids() {
+        // ITM false origin
+        let (easting, northing) = project("itm", 53.5, -8.0).unwrap();
+        assert!((easting - 600000.0).abs() < 0.001);
+        assert!((northing - 750000.0).abs() < 0.001);
+
+        // Dublin
+        let (dublin_easting, dublin_northing) = project("itm", 53.3498, -6.2603).unwrap();
+        assert!((dublin_easting - 715826.507).abs() < 0.01);
+        assert!((dublin_northing - 734697.593).abs() < 0.01);
+
+        // Test out of range
+        assert!(project("itm", 46.9480, 7.4474).is_none());
+        assert!(project("irishgrid", 51.5074, -0.1278).is_none());
+    }
+
+    #[test]
+    fn test_nztm() {
+        // LINZ reference point
+        let (easting, northing) = project("nztm", -34.444066, 172.739194).unwrap();
+        assert!((easting - 1576041.150).abs() < 0.01);
+        assert!((northing - 6188574.240).abs() < 0.01);
+
+        // Test out of range
+        assert!(project("nztm", 51.5074, -0.1278).is_none());
+    }
+
+    #[test]
+    fn test_ups() {
+        let wgs84 = Ellipsoid::WGS84;
+        let (easting, northing) = Projection::ups(true).project(&wgs84, 85.0, 45.0).unwrap();
+        assert!((easting - 2392767.688).abs() < 0.01);
+        assert!((northing - 1607232.312).abs() < 0.01);
+
+        // Mirrored in the south
+        let (south_easting, south_northing) =
+            Projection::ups(false).project(&wgs84, -85.0, 45.0).unwrap();
+        assert!((south_easting - 2392767.688).abs() < 0.01);
+        assert!((south_northing - 2392767.688).abs() < 0.01);
+
+        // Wrong hemisphere
+        assert!(Projection::ups(false).project(&wgs84, 85.0, 45.0).is_none());
+    }
+}
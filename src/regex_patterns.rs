@@ -24,3 +24,12 @@ pub static RE_WIKIPEDIA_LANG_LINK: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#" href="(https?:)//([a-z\-]+)?\.wikipedia\.org/wiki/[^"]*"#)
         .expect("Invalid regex pattern")
 });
+
+/// Regex for linting templates - matches GEOTEMPLATE section starts, other divs, and placeholders.
+/// The section ID may be any attribute of the div, as long as no placeholder comes before it.
+pub static RE_TEMPLATE_LINT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"<div(?:\s(?:[^>{]*?\s)?id="(GEOTEMPLATE-[A-Za-z0-9]+)")?|</div>|\{([A-Za-z0-9_]+)\}|&#123;([A-Za-z0-9_]+)&#125;"#,
+    )
+    .expect("Invalid regex pattern")
+});
@@ -1,10 +1,17 @@
-use crate::{geohack::GeoHack, query_parameters::QueryParameters, templates::Templates};
+use crate::{
+    api::{ApiCoordinates, ApiError, ApiQuery},
+    geo_param_error::GeoParamError,
+    geohack::GeoHack,
+    query_parameters::QueryParameters,
+    template_lint::TemplateLint,
+    templates::Templates,
+};
 use anyhow::Result;
 use axum::{
     Router,
     extract::{Query, State},
     http::{HeaderMap, StatusCode, header::CONTENT_TYPE},
-    response::{AppendHeaders, Html, IntoResponse, Response},
+    response::{AppendHeaders, Html, IntoResponse, Json, Response},
     routing::get,
 };
 use std::net::SocketAddr;
@@ -75,7 +82,7 @@ async fn geohack(
     State(state): State<AppState>,
     headers: HeaderMap,
     params: Query<QueryParameters>,
-) -> Result<Html<String>, StatusCode> {
+) -> Result<Html<String>, Response> {
     let mut query = params.0;
     query.set_http_referrer(
         headers
@@ -83,10 +90,17 @@ async fn geohack(
             .and_then(|v| v.to_str().ok())
             .map(|s| s.to_string()),
     );
-    let mut geohack = GeoHack::new().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    geohack
-        .init_from_query(query.clone())
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let internal_error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let mut geohack = GeoHack::new().map_err(internal_error)?;
+    geohack.init_from_query(query.clone()).map_err(|e| {
+        // Explain bad coordinates to the editor, instead of a blank error page
+        match e.downcast_ref::<GeoParamError>() {
+            Some(err) => {
+                (StatusCode::BAD_REQUEST, Html(err.html_page(query.params()))).into_response()
+            }
+            None => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    })?;
 
     let language = geohack.lang().trim().to_ascii_lowercase();
     let globe = geohack.globe().trim().to_ascii_lowercase();
@@ -95,17 +109,52 @@ async fn geohack(
         .templates
         .load(&language, &globe, &query, purge)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(internal_error)?;
 
     geohack.set_page_content(&template_content);
     let html = geohack
         .process()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(internal_error)?
         .replace("</html>", "<!-- Rust code --></html>");
 
     Ok(Html(html))
 }
 
+#[axum::debug_handler]
+async fn api_coordinates(params: Query<ApiQuery>) -> Result<Json<ApiCoordinates>, ApiError> {
+    Ok(Json(ApiCoordinates::new(&params.0)?))
+}
+
+#[axum::debug_handler]
+async fn api_template_lint(
+    State(state): State<AppState>,
+    params: Query<QueryParameters>,
+) -> Result<Json<TemplateLint>, ApiError> {
+    let internal_error = |e: anyhow::Error| {
+        ApiError::new("internal", &e.to_string()).with_status(StatusCode::INTERNAL_SERVER_ERROR)
+    };
+    let query = params.0;
+    let mut geohack = GeoHack::new().map_err(internal_error)?;
+    geohack
+        .init_from_query(query.clone())
+        .map_err(|e| ApiError::invalid_params(&e))?;
+
+    let language = geohack.lang().trim().to_ascii_lowercase();
+    let globe = geohack.globe().trim().to_ascii_lowercase();
+    let template_content = state
+        .templates
+        .load(&language, &globe, &query, query.purge())
+        .await
+        .map_err(|e| {
+            ApiError::new("template_unavailable", &e.to_string())
+                .with_status(StatusCode::BAD_GATEWAY)
+        })?;
+
+    geohack.set_page_content(&template_content);
+    let lint = geohack.lint_template().map_err(internal_error)?;
+    Ok(Json(lint))
+}
+
 pub async fn run_server(address: [u8; 4], port: u16) -> Result<()> {
     tracing_subscriber::fmt::init();
 
@@ -125,6 +174,8 @@ pub async fn run_server(address: [u8; 4], port: u16) -> Result<()> {
         .route("/lock_icon.gif", get(lock_icon_gif))
         .route("/external.png", get(external_png))
         .route("/testcases.html", get(testcases_html))
+        .route("/api/v1/coordinates", get(api_coordinates))
+        .route("/api/v1/template-lint", get(api_template_lint))
         .layer(TraceLayer::new_for_http())
         .layer(CompressionLayer::new())
         //        .layer(cors),
@@ -0,0 +1,98 @@
+use crate::regex_patterns::RE_TEMPLATE_LINT;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Section name for placeholders that are not inside any GEOTEMPLATE-XX section
+pub const NO_SECTION: &str = "(none)";
+
+/// Placeholders that are deliberately left in the output for other tools to fill in.
+/// `{Plocal}` and `{Ilocal}` are part of the `link=` parameter passed to
+/// //para.toolforge.org/geo/convert/kkj, which fills them with Finnish KKJ coordinates
+/// relative to the `Pref`/`Iref` of each individual link.
+pub const PASS_THROUGH_PLACEHOLDERS: &[&str] = &["Plocal", "Ilocal"];
+
+/// Unresolved `{placeholders}` in a GeoTemplate, grouped by GEOTEMPLATE-XX section
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TemplateLint {
+    /// Section ID => (placeholder => number of occurrences)
+    sections: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl TemplateLint {
+    /// Scan a template, after all known placeholders have been replaced.
+    /// Placeholders are attributed to the innermost GEOTEMPLATE-XX section containing them.
+    pub fn new(text: &str) -> Self {
+        let mut sections: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+        let mut stack: Vec<Option<&str>> = vec![];
+
+        for caps in RE_TEMPLATE_LINT.captures_iter(text) {
+            if let Some(key) = caps.get(2).or_else(|| caps.get(3)) {
+                if PASS_THROUGH_PLACEHOLDERS.contains(&key.as_str()) {
+                    continue;
+                }
+                let section = stack.iter().rev().find_map(|s| *s).unwrap_or(NO_SECTION);
+                *sections
+                    .entry(section.to_string())
+                    .or_default()
+                    .entry(key.as_str().to_string())
+                    .or_default() += 1;
+            } else if caps[0].starts_with("</") {
+                stack.pop();
+            } else {
+                stack.push(caps.get(1).map(|m| m.as_str()));
+            }
+        }
+
+        Self { sections }
+    }
+
+    pub const fn sections(&self) -> &BTreeMap<String, BTreeMap<String, usize>> {
+        &self.sections
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_lint_sections() {
+        let text = r#"{foo}<div id="GEOTEMPLATE-REGIONS"><div class="x">{bar}</div>
+<div id="GEOTEMPLATE-NO">{baz} &#123;baz&#125; <div id="GEOTEMPLATE-SJ">{qux}</div>{quux}</div>
+</div>{Plocal},{Ilocal}"#;
+        let lint = TemplateLint::new(text);
+        let sections = lint.sections();
+        assert_eq!(sections.len(), 4);
+        assert_eq!(sections[NO_SECTION].get("foo"), Some(&1));
+        assert_eq!(sections["GEOTEMPLATE-REGIONS"].get("bar"), Some(&1));
+        assert_eq!(sections["GEOTEMPLATE-NO"].get("baz"), Some(&2));
+        assert_eq!(sections["GEOTEMPLATE-NO"].get("quux"), Some(&1));
+        assert_eq!(sections["GEOTEMPLATE-SJ"].get("qux"), Some(&1));
+        assert!(!sections["GEOTEMPLATE-NO"].contains_key("qux"));
+    }
+
+    #[test]
+    fn test_template_lint_id_attribute() {
+        let text = r#"<div class="x" id="GEOTEMPLATE-DE">{foo}</div>
+<div class="y" data-id="GEOTEMPLATE-AT">{bar}</div>
+<div
+  style="display:none" id="GEOTEMPLATE-CH" title="{baz}">{qux}</div>"#;
+        let lint = TemplateLint::new(text);
+        let sections = lint.sections();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections["GEOTEMPLATE-DE"].get("foo"), Some(&1));
+        assert_eq!(sections[NO_SECTION].get("bar"), Some(&1));
+        assert_eq!(sections["GEOTEMPLATE-CH"].get("baz"), Some(&1));
+        assert_eq!(sections["GEOTEMPLATE-CH"].get("qux"), Some(&1));
+    }
+
+    #[test]
+    fn test_template_lint_empty() {
+        let lint = TemplateLint::new(r#"<div id="GEOTEMPLATE-FI">cp={Plocal},{Ilocal}</div>"#);
+        assert!(lint.is_empty());
+    }
+}
@@ -1,14 +1,20 @@
 use crate::geo_param::GeoParam;
-use crate::traverse_mercator::{CH1903, OSGB36, TransverseMercator};
+use crate::projection::{GRIDS, Grid};
+use crate::traverse_mercator::{MGRS, TransverseMercator};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Default)]
 pub struct TransverseMercatorForms {
-    utm: TransverseMercator,
-    utm33: TransverseMercator,
-    osgb36: OSGB36,
+    /* UTM, or UPS in the polar regions; None for invalid coordinates */
+    utm: Option<TransverseMercator>,
+    mgrs: MGRS,
+    /* Easting and Northing in every registered grid, None outside its area */
+    grids: Vec<(&'static Grid, Option<(f64, f64)>)>,
     osgb36ref: String,
-    ch1903: CH1903,
+    irishgridref: String,
+    /* Gauss-Krüger zone, Easting and Northing, for Germany and Austria */
+    gkzone: String,
+    gauss_krueger: Option<(f64, f64)>,
 }
 
 impl TransverseMercatorForms {
@@ -17,45 +23,95 @@ impl TransverseMercatorForms {
          *  Convert coordinates to various Transverse Mercator forms
          */
 
-        /* standard UTM */
+        /* standard UTM, UPS in the polar regions */
         let mut utm = TransverseMercator::default();
-        utm.lat_lon_to_utm(p.latdeg(), p.londeg());
-        utm.set_zone(utm.lat_lon_to_utm_zone(p.latdeg(), p.londeg()));
-
-        /* fixed UTM as used by iNatur */
-        let mut utm33 = TransverseMercator::default();
-        utm33.lat_lon_zone_to_utm(p.latdeg(), p.londeg(), "33V");
-
-        /*  UK National Grid, see http://www.gps.gov.uk/guide7.asp
-         *  central meridian 47N 2W, offset 100km N 400km W */
-        let mut osgb36 = OSGB36::default();
-        let osgb36ref = osgb36.lat_lon_to_osgb36(p.latdeg(), p.londeg());
-
-        /* Swiss traditional national grid */
-        let mut ch1903 = CH1903::default();
-        ch1903.lat_lon_to_ch1903(p.latdeg(), p.londeg());
-        Self {
+        let utm = utm
+            .lat_lon_to_utm(p.latdeg(), p.londeg())
+            .ok()
+            .map(|()| utm);
+
+        /* Military Grid Reference System, UPS in the polar regions */
+        let mut mgrs = MGRS::default();
+        mgrs.lat_lon_to_mgrs(p.latdeg(), p.londeg());
+
+        /* National grids, see projection.rs */
+        let grids = GRIDS
+            .iter()
+            .map(|grid| (grid, grid.project(p.latdeg(), p.londeg())))
+            .collect();
+
+        let mut ret = Self {
             utm,
-            utm33,
-            osgb36,
-            osgb36ref,
-            ch1903,
+            mgrs,
+            grids,
+            ..Default::default()
+        };
+
+        /* Lettered references for the UK and Irish grids */
+        ret.osgb36ref = ret
+            .position("osgb36")
+            .map(|(e, n)| TransverseMercator::osgb36_grid_ref(e, n))
+            .unwrap_or_default();
+        ret.irishgridref = ret
+            .position("irishgrid")
+            .map(|(e, n)| TransverseMercator::irish_grid_ref(e, n))
+            .unwrap_or_default();
+
+        /* Gauss-Krüger, e.g. zone 3 for Frankfurt, zone M34 for Vienna */
+        let gauss_krueger = p
+            .attributes()
+            .region()
+            .and_then(|region| Grid::gauss_krueger(region.country(), p.latdeg(), p.londeg()));
+        if let Some((grid, position)) = gauss_krueger {
+            ret.gkzone = grid.name().trim_start_matches("gk").to_uppercase();
+            ret.gauss_krueger = Some(position);
         }
+        ret
+    }
+
+    /// Easting and Northing in the registered grid `name`
+    fn position(&self, name: &str) -> Option<(f64, f64)> {
+        self.grids
+            .iter()
+            .find(|(grid, _)| grid.name() == name)
+            .and_then(|(_, position)| *position)
     }
 
     pub fn add_rep_map(&self, rep_map: &mut HashMap<String, String>) {
+        let utm = self.utm.as_ref();
         insert_map!(rep_map, {
-            "utmzone" => self.utm.zone(),
-            "utmnorthing" => self.utm.northing().round(),
-            "utmeasting" => self.utm.easting().round(),
-            "utm33northing" => self.utm33.northing().round(),
-            "utm33easting" => self.utm33.easting().round(),
+            "utmzone" => utm.map(TransverseMercator::zone).unwrap_or_default(),
+            "utmnorthing" => Self::format_metres(utm.map(TransverseMercator::northing)),
+            "utmeasting" => Self::format_metres(utm.map(TransverseMercator::easting)),
+            "mgrs" => self.mgrs.reference(),
+            "mgrs10m" => self.mgrs.reference_with_precision(4),
+            "mgrs100m" => self.mgrs.reference_with_precision(3),
+            "mgrs1km" => self.mgrs.reference_with_precision(2),
+            "mgrs10km" => self.mgrs.reference_with_precision(1),
             "osgb36ref" => &self.osgb36ref,
-            "osgb36northing" => self.osgb36.northing().round(),
-            "osgb36easting" => self.osgb36.easting().round(),
-            "ch1903northing" => self.ch1903.northing().round(),
-            "ch1903easting" => self.ch1903.easting().round(),
+            "irishgridref" => &self.irishgridref,
+        });
+        let rd = self.position("rd");
+        let gk = self.gauss_krueger;
+        insert_map!(rep_map, {
+            "rdx" => Self::format_metres(rd.map(|(x, _)| x)),
+            "rdy" => Self::format_metres(rd.map(|(_, y)| y)),
+            "gkzone" => &self.gkzone,
+            "gkrechts" => Self::format_metres(gk.map(|(rechts, _)| rechts)),
+            "gkhoch" => Self::format_metres(gk.map(|(_, hoch)| hoch)),
         });
+        for (grid, position) in &self.grids {
+            insert_map!(rep_map, {
+                format!("{}easting", grid.name()) => Self::format_metres(position.map(|(e, _)| e)),
+                format!("{}northing", grid.name()) => Self::format_metres(position.map(|(_, n)| n)),
+                format!("{}epsg", grid.name()) => grid.epsg(),
+            });
+        }
+    }
+
+    /// Whole metres, or empty outside the grid
+    fn format_metres(value: Option<f64>) -> String {
+        value.map(|v| v.round().to_string()).unwrap_or_default()
     }
 }
 
@@ -70,7 +126,8 @@ mod tests {
         let tmf = TransverseMercatorForms::new(&geo);
 
         // UTM zone should be 30 or 31 for London
-        assert!(tmf.utm.zone().starts_with("30") || tmf.utm.zone().starts_with("31"));
+        let zone = tmf.utm.as_ref().unwrap().zone();
+        assert!(zone.starts_with("30") || zone.starts_with("31"));
 
         // OSGB36 should produce valid reference for London
         assert!(!tmf.osgb36ref.is_empty());
@@ -84,8 +141,14 @@ mod tests {
         let tmf = TransverseMercatorForms::new(&geo);
 
         // CH1903 should produce valid coordinates for Switzerland
-        assert!(tmf.ch1903.northing() > 100_000.0);
-        assert!(tmf.ch1903.easting() > 500_000.0);
+        let (easting, northing) = tmf.position("ch1903").unwrap();
+        assert!(northing > 100_000.0);
+        assert!(easting > 500_000.0);
+
+        // LV95 as well
+        let (lv95_easting, lv95_northing) = tmf.position("lv95").unwrap();
+        assert!(lv95_northing > 1_100_000.0);
+        assert!(lv95_easting > 2_500_000.0);
     }
 
     #[test]
@@ -105,6 +168,144 @@ mod tests {
         assert!(rep_map.contains_key("osgb36ref"));
         assert!(rep_map.contains_key("ch1903northing"));
         assert!(rep_map.contains_key("ch1903easting"));
+        assert!(rep_map.contains_key("nztmnorthing"));
+        assert!(rep_map.contains_key("nztmeasting"));
+
+        // Every registered grid, empty outside its area
+        for grid in GRIDS {
+            assert!(rep_map.contains_key(&format!("{}easting", grid.name())));
+        }
+        assert_eq!(rep_map.get("ch1903easting").unwrap(), "");
+        assert_eq!(rep_map.get("osgb36northing").unwrap(), "");
+        assert_eq!(rep_map.get("lv95epsg").unwrap(), "2056");
+    }
+
+    #[test]
+    fn test_transverse_mercator_forms_norway() {
+        // Oslo, in UTM zone 32 but shown in the fixed zone 33
+        let geo = GeoParam::new("59.9139_N_10.7522_E").unwrap();
+        let tmf = TransverseMercatorForms::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        tmf.add_rep_map(&mut rep_map);
+
+        assert!(rep_map.get("utmzone").unwrap().starts_with("32"));
+        assert_eq!(rep_map.get("utm33easting").unwrap(), "262560");
+        assert_eq!(rep_map.get("utm33northing").unwrap(), "6649444");
+    }
+
+    #[test]
+    fn test_transverse_mercator_forms_mgrs() {
+        // Washington Monument
+        let geo = GeoParam::new("38.8895_N_77.0352_W").unwrap();
+        let tmf = TransverseMercatorForms::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        tmf.add_rep_map(&mut rep_map);
+
+        assert_eq!(rep_map.get("mgrs").unwrap(), "18SUJ2348606483");
+        assert_eq!(rep_map.get("mgrs10m").unwrap(), "18SUJ23480648");
+        assert_eq!(rep_map.get("mgrs100m").unwrap(), "18SUJ234064");
+        assert_eq!(rep_map.get("mgrs1km").unwrap(), "18SUJ2306");
+        assert_eq!(rep_map.get("mgrs10km").unwrap(), "18SUJ20");
+
+        // Amundsen-Scott South Pole Station
+        let geo2 = GeoParam::new("90_S_0_E").unwrap();
+        let tmf2 = TransverseMercatorForms::new(&geo2);
+        assert_eq!(tmf2.mgrs.reference(), "BAN0000000000");
+    }
+
+    #[test]
+    fn test_transverse_mercator_forms_ireland() {
+        // Dublin
+        let geo = GeoParam::new("53.3498_N_6.2603_W").unwrap();
+        let tmf = TransverseMercatorForms::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        tmf.add_rep_map(&mut rep_map);
+
+        assert!(rep_map.get("irishgridref").unwrap().starts_with('O'));
+        assert_eq!(rep_map.get("itmeasting").unwrap(), "715827");
+        assert_eq!(rep_map.get("itmnorthing").unwrap(), "734698");
+
+        // Outside Ireland
+        let geo2 = GeoParam::new("51.5074_N_0.1278_W").unwrap();
+        let tmf2 = TransverseMercatorForms::new(&geo2);
+        assert!(tmf2.irishgridref.is_empty());
+    }
+
+    #[test]
+    fn test_transverse_mercator_forms_local_grids() {
+        // Amersfoort, at the origin of RD New
+        let geo = GeoParam::new("52.15517440_N_5.38720621_E").unwrap();
+        let mut rep_map = HashMap::new();
+        TransverseMercatorForms::new(&geo).add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("rdx").unwrap(), "155000");
+        assert_eq!(rep_map.get("rdy").unwrap(), "463000");
+        assert_eq!(rep_map.get("rdeasting").unwrap(), "155000");
+        assert_eq!(rep_map.get("gkzone").unwrap(), "");
+
+        // Frankfurt am Main
+        let frankfurt = GeoParam::new("50.1109_N_8.6821_E_region:DE-HE").unwrap();
+        let mut frankfurt_map = HashMap::new();
+        TransverseMercatorForms::new(&frankfurt).add_rep_map(&mut frankfurt_map);
+        assert_eq!(frankfurt_map.get("gkzone").unwrap(), "3");
+        assert_eq!(frankfurt_map.get("gkrechts").unwrap(), "3477335");
+        assert_eq!(frankfurt_map.get("gkhoch").unwrap(), "5552791");
+        assert_eq!(frankfurt_map.get("rdx").unwrap(), "");
+
+        // Salzburg, inside both the German and the Austrian zones
+        let salzburg = GeoParam::new("47.8095_N_13.0550_E_region:AT-5").unwrap();
+        let mut salzburg_map = HashMap::new();
+        TransverseMercatorForms::new(&salzburg).add_rep_map(&mut salzburg_map);
+        assert_eq!(salzburg_map.get("gkzone").unwrap(), "M31");
+
+        // Outside Germany and Austria
+        let paris = GeoParam::new("48.8530_N_2.3499_E_region:FR").unwrap();
+        let mut paris_map = HashMap::new();
+        TransverseMercatorForms::new(&paris).add_rep_map(&mut paris_map);
+        assert_eq!(paris_map.get("gkzone").unwrap(), "");
+        assert_eq!(paris_map.get("gkrechts").unwrap(), "");
+
+        // Zürich is inside the German zones, but not in Germany
+        let zurich = GeoParam::new("47.3769_N_8.5417_E_region:CH").unwrap();
+        let mut zurich_map = HashMap::new();
+        TransverseMercatorForms::new(&zurich).add_rep_map(&mut zurich_map);
+        assert_eq!(zurich_map.get("gkzone").unwrap(), "");
+
+        // Without a region, the country is unknown
+        let frankfurt_no_region = GeoParam::new("50.1109_N_8.6821_E").unwrap();
+        let mut no_region_map = HashMap::new();
+        TransverseMercatorForms::new(&frankfurt_no_region).add_rep_map(&mut no_region_map);
+        assert_eq!(no_region_map.get("gkzone").unwrap(), "");
+        assert_eq!(no_region_map.get("gkrechts").unwrap(), "");
+    }
+
+    #[test]
+    fn test_transverse_mercator_forms_new_zealand() {
+        // Auckland
+        let geo = GeoParam::new("36_51_S_174_47_E").unwrap();
+        let tmf = TransverseMercatorForms::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        tmf.add_rep_map(&mut rep_map);
+
+        assert_eq!(rep_map.get("nztmeasting").unwrap(), "1758992");
+        assert_eq!(rep_map.get("nztmnorthing").unwrap(), "5920283");
+    }
+
+    #[test]
+    fn test_transverse_mercator_forms_polar() {
+        // Amundsen-Scott South Pole Station, UPS zone A for western longitudes
+        let geo = GeoParam::new("90_S_139.27_W").unwrap();
+        let tmf = TransverseMercatorForms::new(&geo);
+
+        let mut rep_map = HashMap::new();
+        tmf.add_rep_map(&mut rep_map);
+
+        assert_eq!(rep_map.get("utmzone").unwrap(), "A");
+        assert_eq!(rep_map.get("utmeasting").unwrap(), "2000000");
+        assert_eq!(rep_map.get("utmnorthing").unwrap(), "2000000");
     }
 
     #[test]
@@ -115,5 +316,6 @@ mod tests {
 
         // OSGB36 is only valid for UK
         assert!(tmf.osgb36ref.is_empty());
+        assert!(tmf.position("osgb36").is_none());
     }
 }
@@ -14,6 +14,11 @@
  *  http://search.cpan.org/src/GRAHAMC/Geo-Coordinates-UTM-0.05/
  *  UK Ordnance Survey grid (OSBG36): http://www.gps.gov.uk/guidecontents.asp
  *  Swiss CH1903: http://www.gps.gov.uk/guidecontents.asp
+ *  Swiss LV95: https://www.swisstopo.admin.ch/en/knowledge-facts/surveying-geodesy/reference-frames.html
+ *  Irish Grid and ITM: https://www.osi.ie/wp-content/uploads/2015/05/transformations_booklet.pdf
+ *  Datum shifts from WGS-84: see datum.rs
+ *  MGRS and UPS: https://earth-info.nga.mil/php/download.php?file=coord-grids
+ *  Krüger n-series: https://arxiv.org/abs/1002.1417
  *
  *  ----------------------------------------------------------------------
  *
@@ -34,6 +39,9 @@
  *  along with this program; if not, write to the Free Software
  *  Foundation, Inc., 59 Temple Place, Suite 330, Boston, MA  02111-1307  USA
  */
+use crate::datum::{Ellipsoid, Helmert};
+use crate::grid_error::GridError;
+use crate::projection::Projection;
 use std::f64::consts::PI;
 
 /**
@@ -78,6 +86,26 @@ impl Default for TransverseMercator {
 }
 
 impl TransverseMercator {
+    /// Transverse Mercator on another ellipsoid, without a Southern hemisphere offset
+    pub const fn new(
+        ellipsoid: &Ellipsoid,
+        scale: f64,
+        easting_offset: f64,
+        northing_offset: f64,
+    ) -> Self {
+        Self {
+            northing: 0.0,
+            easting: 0.0,
+            zone: String::new(),
+            radius: ellipsoid.radius(),
+            eccentricity: ellipsoid.eccentricity(),
+            scale,
+            easting_offset,
+            northing_offset,
+            northing_offset_south: 0.0,
+        }
+    }
+
     pub const fn northing(&self) -> f64 {
         self.northing
     }
@@ -90,17 +118,20 @@ impl TransverseMercator {
         &self.zone
     }
 
-    pub fn set_zone(&mut self, zone: String) {
-        self.zone = zone;
-    }
-
     /**
      *  Convert latitude, longitude in decimal degrees to
-     *  UTM Zone, Easting, and Northing
+     *  UTM Zone, Easting, and Northing.
+     *  Uses UPS for the polar regions, with the zone set to A, B, Y or Z.
      */
-    pub fn lat_lon_to_utm(&mut self, latitude: f64, longitude: f64) {
-        self.zone = self.lat_lon_to_utm_zone(latitude, longitude);
-        self.lat_lon_zone_to_utm(latitude, longitude, &self.zone.clone());
+    pub fn lat_lon_to_utm(&mut self, latitude: f64, longitude: f64) -> Result<(), GridError> {
+        if (-80.0..=84.0).contains(&latitude) {
+            let zone = self.lat_lon_to_utm_zone(latitude, longitude);
+            self.lat_lon_zone_to_utm(latitude, longitude, &zone)?;
+            self.zone = zone;
+            Ok(())
+        } else {
+            self.lat_lon_to_ups(latitude, longitude)
+        }
     }
 
     /**
@@ -136,39 +167,177 @@ impl TransverseMercator {
 
     /**
      *  Convert latitude, longitude in decimal degrees to
-     *  UTM Easting and Northing in a specific zone
-     *
-     *  \return false if problems
+     *  UTM Easting and Northing in a specific zone, e.g. "33V".
+     *  The false northing for the Southern hemisphere is taken from the zone letter,
+     *  so a forced zone keeps its hemisphere.
      */
-    pub fn lat_lon_zone_to_utm(&mut self, latitude: f64, longitude: f64, zone: &str) -> bool {
-        self.lat_lon_origin_to_tm(latitude, longitude, 0.0, Self::utmzone_origin(zone))
+    pub fn lat_lon_zone_to_utm(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        zone: &str,
+    ) -> Result<(), GridError> {
+        if !(-80.0..=84.0).contains(&latitude) {
+            return Err(GridError::OutsideUtm(latitude));
+        }
+        let (zone_num, letter) = Self::parse_utm_zone(zone)?;
+        self.lat_lon_origin_to_tm(latitude, longitude, 0.0, Self::utmzone_origin(zone_num))?;
+        if letter < 'N' {
+            self.northing += self.northing_offset_south;
+        }
+        Ok(())
+    }
+
+    /**
+     *  Split a UTM zone (e.g. "32U") into its number and latitude band letter
+     */
+    fn parse_utm_zone(zone: &str) -> Result<(i32, char), GridError> {
+        let invalid = || GridError::InvalidZone(zone.to_string());
+        let digits: String = zone.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let zone_num = digits
+            .parse::<i32>()
+            .ok()
+            .filter(|z| (1..=60).contains(z))
+            .ok_or_else(invalid)?;
+        let mut rest = zone[digits.len()..].chars();
+        let letter = match (rest.next(), rest.next()) {
+            (Some(letter), None) => letter.to_ascii_uppercase(),
+            _ => return Err(invalid()),
+        };
+        if !"CDEFGHJKLMNPQRSTUVWX".contains(letter) {
+            return Err(invalid());
+        }
+        Ok((zone_num, letter))
     }
 
     /**
      *  Convert latitude, longitude in decimal degrees to
-     *  OSBG36 Easting and Northing
+     *  Universal Polar Stereographic (UPS) Easting and Northing
+     *  for latitudes north of 84 or south of -80.
+     *  The zone is set to the UPS hemisphere letter, A/B for south, Y/Z for north.
      */
-    pub fn lat_lon_to_osgb36(&mut self, latitude: f64, longitude: f64) -> String {
-        /* Airy 1830 ellipsoid */
-        self.radius = 6377563.396;
-        /* inverse flattening 1/f: 299.3249647 */
-        self.eccentricity = 0.00667054; /* square of eccentricity */
+    pub fn lat_lon_to_ups(&mut self, latitude: f64, longitude: f64) -> Result<(), GridError> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(GridError::InvalidCoordinates {
+                latitude,
+                longitude,
+            });
+        }
+        if (-80.0..=84.0).contains(&latitude) {
+            // UPS not defined in this range
+            return Err(GridError::OutsideUps(latitude));
+        }
 
-        self.scale = 0.9996013;
-        self.easting_offset = 400000.0;
-        self.northing_offset = -100000.0;
-        self.northing_offset_south = 0.0;
+        let north = latitude > 0.0;
+        let ellipsoid = Ellipsoid::new(self.radius, self.eccentricity);
+        let (easting, northing) = Projection::ups(north)
+            .project(&ellipsoid, latitude, longitude)
+            .ok_or(GridError::OutsideUps(latitude))?;
+        self.easting = easting;
+        self.northing = northing;
+        self.zone = match (north, longitude < 0.0) {
+            (false, true) => "A",
+            (false, false) => "B",
+            (true, true) => "Y",
+            (true, false) => "Z",
+        }
+        .to_string();
 
-        let latitude_origin = 49.0;
-        let longitude_origin = -2.0;
+        Ok(())
+    }
 
-        if !self.lat_lon_origin_to_tm(latitude, longitude, latitude_origin, longitude_origin) {
+    /**
+     *  Convert latitude, longitude in decimal degrees to a
+     *  Military Grid Reference System (MGRS) reference with 1 m precision,
+     *  e.g. 18SUJ2348606483. Uses UPS for the polar regions.
+     */
+    pub fn lat_lon_to_mgrs(&mut self, latitude: f64, longitude: f64) -> String {
+        if self.lat_lon_to_ups(latitude, longitude).is_ok() {
+            return self.ups_to_mgrs();
+        }
+
+        if self.lat_lon_to_utm(latitude, longitude).is_err() {
             return String::new();
         }
 
+        let zone_num = self
+            .zone
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse::<usize>()
+            .unwrap_or(1);
+
+        /* 100 km square column letters repeat every three zones, without I and O */
+        let column_letters = ["ABCDEFGH", "JKLMNPQR", "STUVWXYZ"][(zone_num - 1) % 3];
+        /* 100 km square row letters repeat every 2000 km, offset by 5 in even zones */
+        let row_letters = "ABCDEFGHJKLMNPQRSTUV";
+
+        let grid_x = (self.easting / 100000.0).floor() as usize;
+        let grid_y = (self.northing / 100000.0).floor() as usize;
+        let row_offset = if zone_num % 2 == 0 { 5 } else { 0 };
+
+        let c1 = column_letters
+            .chars()
+            .nth(grid_x.wrapping_sub(1))
+            .unwrap_or('X');
+        let c2 = row_letters
+            .chars()
+            .nth((grid_y + row_offset) % 20)
+            .unwrap_or('X');
+
+        format!("{}{}{}{}", self.zone, c1, c2, self.mgrs_digits())
+    }
+
+    /**
+     *  MGRS reference for the current UPS Easting and Northing
+     */
+    fn ups_to_mgrs(&self) -> String {
+        let north = self.zone == "Y" || self.zone == "Z";
+        let east = self.zone == "B" || self.zone == "Z";
+
+        /* 100 km square letters, per hemisphere and west/east half */
+        let (column_letters, row_letters, min_index) = match (north, east) {
+            (false, false) => ("JKLPQRSTUXYZ", "ABCDEFGHJKLMNPQRSTUVWXYZ", 8),
+            (false, true) => ("ABCFGHJKLPQR", "ABCDEFGHJKLMNPQRSTUVWXYZ", 8),
+            (true, false) => ("RSTUXYZ", "ABCDEFGHJKLMNP", 13),
+            (true, true) => ("ABCFGHJ", "ABCDEFGHJKLMNP", 13),
+        };
+
+        let grid_x = (self.easting / 100000.0).floor() as usize;
+        let grid_y = (self.northing / 100000.0).floor() as usize;
+        let column = if east {
+            grid_x.wrapping_sub(20)
+        } else {
+            grid_x.wrapping_sub(min_index)
+        };
+
+        let c1 = column_letters.chars().nth(column).unwrap_or('X');
+        let c2 = row_letters
+            .chars()
+            .nth(grid_y.wrapping_sub(min_index))
+            .unwrap_or('X');
+
+        format!("{}{}{}{}", self.zone, c1, c2, self.mgrs_digits())
+    }
+
+    /**
+     *  Easting and Northing within the 100 km square, 5 digits each
+     */
+    fn mgrs_digits(&self) -> String {
+        let e = format!("{:05}", self.easting as i64 % 100000);
+        let n = format!("{:05}", self.northing as i64 % 100000);
+        format!("{}{}", e, n)
+    }
+
+    /**
+     *  Lettered OSGB36 grid reference (e.g. TQ3003580414) for an Easting and Northing,
+     *  empty outside the grid
+     */
+    pub fn osgb36_grid_ref(easting: f64, northing: f64) -> String {
         /* fix by Roger W Haworth */
-        let grid_x = (self.easting / 100000.0).floor() as i32;
-        let grid_y = (self.northing / 100000.0).floor() as i32;
+        let grid_x = (easting / 100000.0).floor() as i32;
+        let grid_y = (northing / 100000.0).floor() as i32;
 
         if !(0..=6).contains(&grid_x) || !(0..=12).contains(&grid_y) {
             /* outside area for OSGB36 */
@@ -185,97 +354,119 @@ impl TransverseMercator {
         let c1 = letters.chars().nth(c1_index as usize).unwrap_or('X');
         let c2 = letters.chars().nth(c2_index as usize).unwrap_or('X');
 
-        let e = format!("{:05}", self.easting as i32 % 100000);
-        let n = format!("{:05}", self.northing as i32 % 100000);
+        let e = format!("{:05}", easting as i32 % 100000);
+        let n = format!("{:05}", northing as i32 % 100000);
 
         format!("{}{}{}{}", c1, c2, e, n)
     }
 
     /**
-     *  Convert latitude, longitude in decimal degrees to
-     *  CH1903 Easting and Northing
-     *  Assumed range is latitude 45.5 .. 48 and logitude 5 - 11
-     *  Code by [[de:Benutzer:Meleager]]
+     *  Lettered Irish Grid reference (e.g. O1590034671) for an Easting and Northing,
+     *  empty outside the grid
+     *  See https://www.osi.ie/wp-content/uploads/2015/05/transformations_booklet.pdf
      */
-    pub fn lat_lon_to_ch1903(&mut self, latitude: f64, longitude: f64) -> bool {
-        if !(45.5..=48.0).contains(&latitude) || !(5.0..=11.0).contains(&longitude) {
-            /* outside reasonable range */
-            self.easting = 0.0;
-            self.northing = 0.0;
-            return false;
-        }
-
-        /* Approximation formula according to  */
-        /* http://www.swisstopo.ch/pub/down/basics/geo/system/swiss_projection_de.pdf */
-        /* chapter 4.1, page 11. */
+    pub fn irish_grid_ref(easting: f64, northing: f64) -> String {
+        let grid_x = (easting / 100000.0).floor() as i32;
+        let grid_y = (northing / 100000.0).floor() as i32;
 
-        let ps = latitude * 3600.0;
-        let ls = longitude * 3600.0;
+        if !(0..=4).contains(&grid_x) || !(0..=4).contains(&grid_y) {
+            /* outside area for Irish Grid */
+            return String::new();
+        }
 
-        let pp = (ps - 169028.66) / 10000.0;
-        let lp = (ls - 26782.5) / 10000.0;
+        /*             0000000000111111111122222 */
+        /*             0123456789012345678901234 */
+        let letters = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
 
-        self.northing = 200147.07 + 308807.95 * pp + 3745.25 * lp * lp + 76.63 * pp * pp
-            - 194.56 * lp * lp * pp
-            + 119.79 * pp * pp * pp;
+        let c_index = (4 - grid_y) * 5 + grid_x;
+        let c = letters.chars().nth(c_index as usize).unwrap_or('X');
 
-        self.easting = 600072.37 + 211455.93 * lp
-            - 10938.51 * lp * pp
-            - 0.36 * lp * pp * pp
-            - 44.54 * lp * lp * lp;
+        let e = format!("{:05}", easting as i32 % 100000);
+        let n = format!("{:05}", northing as i32 % 100000);
 
-        true
+        format!("{}{}{}", c, e, n)
     }
 
-    /*	Kvalberg code
-        function LatLon2CH1903( $latitude, $longitude )
-        {
-            if ($latitude < 45.5 or $latitude > 48
-             or $longitude < 5.0 or $longitude > 11) {
-                // outside reasonable range
-                $this->Easting = "";
-                $this->Northing = "";
-                return false;
-            }
-
-            // ellipsoid: Bessel 1841
-            $this->Radius = 6377397.155;
-            // 299.1528128
-            $this->Eccentricity = 0.006674372;
-
-            $this->Scale = 1.0;
-            $this->Easting_Offset =   600000.0;
-            $this->Northing_Offset =  200000.0;
-            $this->Northing_Offset_South = 0.0;
-
-            $latitude_origin  = 46.95240555556;
-            $longitude_origin =  7.43958333333;
-
-            $this->LatLonOrigin2TM( $latitude, $longitude,
-                    $latitude_origin, $longitude_origin );
-        }
-    */
+    const fn utmzone_origin(zone_num: i32) -> f64 {
+        (zone_num - 1) as f64 * 6.0 - 180.0 + 3.0
+    }
 
-    fn utmzone_origin(zone: &str) -> f64 {
-        let zone_num = zone
-            .chars()
-            .take_while(|c| c.is_ascii_digit())
-            .collect::<String>()
-            .parse::<i32>()
-            .unwrap_or(1);
+    /**
+     *  Third flattening n and rectifying radius A of the ellipsoid
+     */
+    fn third_flattening(&self) -> (f64, f64) {
+        let f = 1.0 - (1.0 - self.eccentricity).sqrt();
+        let n = f / (2.0 - f);
+        let n2 = n * n;
+        let a = self.radius / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0 + n2 * n2 * n2 / 256.0);
+        (n, a)
+    }
 
-        (zone_num - 1) as f64 * 6.0 - 180.0 + 3.0
+    /**
+     *  Krüger series coefficients, to sixth order in n, from conformal to rectifying
+     *  coordinates (alpha) and back (beta)
+     */
+    fn kruger_coefficients(n: f64) -> ([f64; 6], [f64; 6]) {
+        let n2 = n * n;
+        let n3 = n2 * n;
+        let n4 = n3 * n;
+        let n5 = n4 * n;
+        let n6 = n5 * n;
+        let alpha = [
+            n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0 + 41.0 * n4 / 180.0 - 127.0 * n5 / 288.0
+                + 7891.0 * n6 / 37800.0,
+            13.0 * n2 / 48.0 - 3.0 * n3 / 5.0 + 557.0 * n4 / 1440.0 + 281.0 * n5 / 630.0
+                - 1983433.0 * n6 / 1935360.0,
+            61.0 * n3 / 240.0 - 103.0 * n4 / 140.0
+                + 15061.0 * n5 / 26880.0
+                + 167603.0 * n6 / 181440.0,
+            49561.0 * n4 / 161280.0 - 179.0 * n5 / 168.0 + 6601661.0 * n6 / 7257600.0,
+            34729.0 * n5 / 80640.0 - 3418889.0 * n6 / 1995840.0,
+            212378941.0 * n6 / 319334400.0,
+        ];
+        let beta = [
+            n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0 - n4 / 360.0 - 81.0 * n5 / 512.0
+                + 96199.0 * n6 / 604800.0,
+            n2 / 48.0 + n3 / 15.0 - 437.0 * n4 / 1440.0 + 46.0 * n5 / 105.0
+                - 1118711.0 * n6 / 3870720.0,
+            17.0 * n3 / 480.0 - 37.0 * n4 / 840.0 - 209.0 * n5 / 4480.0 + 5569.0 * n6 / 90720.0,
+            4397.0 * n4 / 161280.0 - 11.0 * n5 / 504.0 - 830251.0 * n6 / 7257600.0,
+            4583.0 * n5 / 161280.0 - 108847.0 * n6 / 3991680.0,
+            20648693.0 * n6 / 638668800.0,
+        ];
+        (alpha, beta)
     }
 
-    fn find_m(&self, lat_rad: f64) -> f64 {
-        let e = self.eccentricity;
+    /**
+     *  Add the Krüger series to (xi, eta), with sign 1 for alpha and -1 for beta
+     */
+    fn kruger_series(xi: f64, eta: f64, coefficients: &[f64; 6], sign: f64) -> (f64, f64) {
+        coefficients
+            .iter()
+            .enumerate()
+            .fold((xi, eta), |(x, y), (j, c)| {
+                let k = 2.0 * (j + 1) as f64;
+                (
+                    x + sign * c * (k * xi).sin() * (k * eta).cosh(),
+                    y + sign * c * (k * xi).cos() * (k * eta).sinh(),
+                )
+            })
+    }
 
-        self.radius
-            * ((1.0 - e / 4.0 - 3.0 * e * e / 64.0 - 5.0 * e * e * e / 256.0) * lat_rad
-                - (3.0 * e / 8.0 + 3.0 * e * e / 32.0 + 45.0 * e * e * e / 1024.0)
-                    * (2.0 * lat_rad).sin()
-                + (15.0 * e * e / 256.0 + 45.0 * e * e * e / 1024.0) * (4.0 * lat_rad).sin()
-                - (35.0 * e * e * e / 3072.0) * (6.0 * lat_rad).sin())
+    /**
+     *  Rectifying coordinates (xi, eta), i.e. Northing and Easting divided by A,
+     *  for a latitude and a longitude relative to the central meridian, in radians
+     */
+    fn lat_lon_to_xi_eta(&self, lat_rad: f64, lon_rad: f64, alpha: &[f64; 6]) -> (f64, f64) {
+        let e = self.eccentricity.sqrt();
+        /* conformal latitude, as tan */
+        let tau = lat_rad.tan();
+        let sigma = (e * (e * tau / tau.hypot(1.0)).atanh()).sinh();
+        let tau_prime = tau * sigma.hypot(1.0) - sigma * tau.hypot(1.0);
+
+        let xi_prime = tau_prime.atan2(lon_rad.cos());
+        let eta_prime = (lon_rad.sin() / tau_prime.hypot(lon_rad.cos())).asinh();
+        Self::kruger_series(xi_prime, eta_prime, alpha, 1.0)
     }
 
     fn deg2rad(deg: f64) -> f64 {
@@ -285,8 +476,9 @@ impl TransverseMercator {
     /**
      *  Convert latitude, longitude in decimal degrees to
      *  TM Easting and Northing based on a specified origin
-     *
-     *  \return false if problems
+     *  Uses the Krüger n-series to sixth order, see Karney,
+     *  "Transverse Mercator with an accuracy of a few nanometers", J. Geodesy 85 (2011),
+     *  accurate to well below a millimetre within 30° of the central meridian
      */
     pub fn lat_lon_origin_to_tm(
         &mut self,
@@ -294,146 +486,319 @@ impl TransverseMercator {
         longitude: f64,
         latitude_origin: f64,
         longitude_origin: f64,
-    ) -> bool {
-        if !(-180.0..=180.0).contains(&longitude) || !(-80.0..=84.0).contains(&latitude) {
-            // UTM not defined in this range
-            return false;
+    ) -> Result<(), GridError> {
+        if !(-180.0..=180.0).contains(&longitude) || !(-90.0..=90.0).contains(&latitude) {
+            return Err(GridError::InvalidCoordinates {
+                latitude,
+                longitude,
+            });
         }
 
-        let longitude2 = longitude - ((longitude + 180.0) / 360.0).floor() * 360.0;
+        /* longitude relative to the central meridian, in [-180,180) */
+        let longitude2 = (longitude - longitude_origin + 180.0).rem_euclid(360.0) - 180.0;
 
-        let lat_rad = Self::deg2rad(latitude);
+        let (n, a) = self.third_flattening();
+        let (alpha, _) = Self::kruger_coefficients(n);
+        let (xi, eta) =
+            self.lat_lon_to_xi_eta(Self::deg2rad(latitude), Self::deg2rad(longitude2), &alpha);
+        let (xi0, _) = self.lat_lon_to_xi_eta(Self::deg2rad(latitude_origin), 0.0, &alpha);
 
-        let e = self.eccentricity;
-        let e_prime_sq = e / (1.0 - e);
+        self.northing = self.northing_offset + self.scale * a * (xi - xi0);
+        self.easting = self.easting_offset + self.scale * a * eta;
 
-        let v = self.radius / (1.0 - e * lat_rad.sin() * lat_rad.sin()).sqrt();
-        let t_val = lat_rad.tan().powi(2);
-        let c = e_prime_sq * lat_rad.cos().powi(2);
-        let a = Self::deg2rad(longitude2 - longitude_origin) * lat_rad.cos();
-        let m = self.find_m(lat_rad);
+        Ok(())
+    }
 
-        let m0 = if latitude_origin != 0.0 {
-            self.find_m(Self::deg2rad(latitude_origin))
-        } else {
-            0.0
-        };
+    fn rad2deg(rad: f64) -> f64 {
+        (180.0 / PI) * rad
+    }
+
+    /**
+     *  Convert TM Easting and Northing based on a specified origin to
+     *  latitude, longitude in decimal degrees.
+     *  The Northing must not include the Southern hemisphere offset.
+     */
+    pub fn tm_origin_to_lat_lon(
+        &self,
+        easting: f64,
+        northing: f64,
+        latitude_origin: f64,
+        longitude_origin: f64,
+    ) -> (f64, f64) {
+        let (n, a) = self.third_flattening();
+        let (alpha, beta) = Self::kruger_coefficients(n);
+        let (xi0, _) = self.lat_lon_to_xi_eta(Self::deg2rad(latitude_origin), 0.0, &alpha);
+
+        let xi = xi0 + (northing - self.northing_offset) / (self.scale * a);
+        let eta = (easting - self.easting_offset) / (self.scale * a);
+        let (xi_prime, eta_prime) = Self::kruger_series(xi, eta, &beta, -1.0);
+
+        /* conformal latitude, as tan, and longitude */
+        let tau_prime = xi_prime.sin() / eta_prime.sinh().hypot(xi_prime.cos());
+        let lon_rad = eta_prime.sinh().atan2(xi_prime.cos());
+
+        /* back to the geodetic latitude, by Newton's method */
+        let e2 = self.eccentricity;
+        let e = e2.sqrt();
+        let mut tau = tau_prime;
+        for _ in 0..5 {
+            let sigma = (e * (e * tau / tau.hypot(1.0)).atanh()).sinh();
+            let tau_i = tau * sigma.hypot(1.0) - sigma * tau.hypot(1.0);
+            tau += (tau_prime - tau_i) / tau_i.hypot(1.0) * (1.0 + (1.0 - e2) * tau * tau)
+                / ((1.0 - e2) * tau.hypot(1.0));
+        }
+
+        (
+            Self::rad2deg(tau.atan()),
+            longitude_origin + Self::rad2deg(lon_rad),
+        )
+    }
 
-        let northing = self.northing_offset
-            + self.scale
-                * ((m - m0)
-                    + v * lat_rad.tan()
-                        * (a * a / 2.0
-                            + (5.0 - t_val + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
-                            + (61.0 - 58.0 * t_val + t_val * t_val + 600.0 * c
-                                - 330.0 * e_prime_sq)
-                                * a.powi(6)
-                                / 720.0));
-
-        let easting = self.easting_offset
-            + self.scale
-                * v
-                * (a + (1.0 - t_val + c) * a.powi(3) / 6.0
-                    + (5.0 - 18.0 * t_val + t_val.powi(2) + 72.0 * c - 58.0 * e_prime_sq)
-                        * a.powi(5)
-                        / 120.0);
-
-        // FIXME: Uze zone_letter
-        // if (ord($zone_letter) < ord('N'))
-        let northing = if latitude < 0.0 {
-            northing + self.northing_offset_south
+    /**
+     *  Convert UTM Easting and Northing in a zone (e.g. "32U") to
+     *  latitude, longitude in decimal degrees.
+     *  The hemisphere is taken from the zone letter.
+     */
+    pub fn utm_to_lat_lon(&self, easting: f64, northing: f64, zone: &str) -> Option<(f64, f64)> {
+        let (zone_num, letter) = Self::parse_utm_zone(zone).ok()?;
+        let northing = if letter < 'N' {
+            northing - self.northing_offset_south
         } else {
             northing
         };
+        Some(self.tm_origin_to_lat_lon(easting, northing, 0.0, Self::utmzone_origin(zone_num)))
+    }
 
-        self.northing = northing;
-        self.easting = easting;
+    /**
+     *  Convert UPS Easting and Northing to latitude, longitude in decimal degrees
+     */
+    pub fn ups_to_lat_lon(&self, easting: f64, northing: f64, north: bool) -> (f64, f64) {
+        let e = self.eccentricity.sqrt();
+        let dx = easting - 2000000.0;
+        let dy = northing - 2000000.0;
+        let rho = dx.hypot(dy);
+        let t = rho * ((1.0 + e).powf(1.0 + e) * (1.0 - e).powf(1.0 - e)).sqrt()
+            / (2.0 * self.radius * 0.994);
+
+        /* iterate for the conformal latitude */
+        let mut lat_rad = PI / 2.0 - 2.0 * t.atan();
+        for _ in 0..10 {
+            lat_rad = PI / 2.0
+                - 2.0
+                    * (t * ((1.0 - e * lat_rad.sin()) / (1.0 + e * lat_rad.sin())).powf(e / 2.0))
+                        .atan();
+        }
 
-        true
+        if north {
+            (Self::rad2deg(lat_rad), Self::rad2deg(dx.atan2(-dy)))
+        } else {
+            (-Self::rad2deg(lat_rad), Self::rad2deg(dx.atan2(dy)))
+        }
     }
-}
 
-// Additional structures for specialized coordinate systems
-#[derive(Debug, Clone)]
-pub struct OSGB36 {
-    tm: TransverseMercator,
-    northing: f64,
-    easting: f64,
-}
+    /**
+     *  Convert a MGRS reference (e.g. 18SUJ2348606483, or 18SUJ2306 for 1 km precision)
+     *  to latitude, longitude in decimal degrees, at the south-west corner of the square
+     */
+    pub fn mgrs_to_lat_lon(&self, reference: &str) -> Option<(f64, f64)> {
+        let reference = reference.trim().to_ascii_uppercase();
+        let zone_digits: String = reference
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        let rest: Vec<char> = reference[zone_digits.len()..].chars().collect();
+        if rest.len() < 3 || rest[3..].iter().any(|c| !c.is_ascii_digit()) {
+            return None;
+        }
+        let (easting, northing) = Self::mgrs_digits_to_offsets(&rest[3..])?;
 
-impl Default for OSGB36 {
-    fn default() -> Self {
-        let tm = TransverseMercator {
-            radius: 6377563.396,      // Airy 1830 ellipsoid
-            eccentricity: 0.00667054, // square of eccentricity
-            scale: 0.9996013,         // inverse flattening 1/f: 299.3249647
-            easting_offset: 400000.0,
-            northing_offset: -100000.0,
-            northing_offset_south: 0.0,
-            ..Default::default()
+        if zone_digits.is_empty() {
+            return self.ups_mgrs_to_lat_lon(rest[0], rest[1], rest[2], easting, northing);
+        }
+
+        let zone_num = zone_digits
+            .parse::<usize>()
+            .ok()
+            .filter(|z| (1..=60).contains(z))?;
+        let band = rest[0];
+        let band_index = "CDEFGHJKLMNPQRSTUVWX".find(band)?;
+
+        let column_letters = ["ABCDEFGH", "JKLMNPQR", "STUVWXYZ"][(zone_num - 1) % 3];
+        let row_letters = "ABCDEFGHJKLMNPQRSTUV";
+        let row_offset = if zone_num % 2 == 0 { 5 } else { 0 };
+        let column = column_letters.find(rest[1])? + 1;
+        let row = (row_letters.find(rest[2])? + 20 - row_offset) % 20;
+
+        let easting = column as f64 * 100000.0 + easting;
+        let northing = row as f64 * 100000.0 + northing;
+
+        /* The row letters repeat every 2000 km; pick the northing that falls into the band */
+        let zone = format!("{}{}", zone_num, band);
+        let band_middle = -80.0 + 8.0 * band_index as f64 + 4.0;
+        (0..6)
+            .filter_map(|k| self.utm_to_lat_lon(easting, northing + k as f64 * 2000000.0, &zone))
+            .min_by(|a, b| {
+                (a.0 - band_middle)
+                    .abs()
+                    .total_cmp(&(b.0 - band_middle).abs())
+            })
+            .filter(|(lat, _)| (lat - band_middle).abs() <= 5.0)
+    }
+
+    /**
+     *  Convert a MGRS reference in the polar regions to latitude, longitude in decimal degrees
+     */
+    fn ups_mgrs_to_lat_lon(
+        &self,
+        zone: char,
+        c1: char,
+        c2: char,
+        easting: f64,
+        northing: f64,
+    ) -> Option<(f64, f64)> {
+        let north = zone == 'Y' || zone == 'Z';
+        let east = zone == 'B' || zone == 'Z';
+        if !north && zone != 'A' && zone != 'B' {
+            return None;
+        }
+        let (column_letters, row_letters, min_index) = match (north, east) {
+            (false, false) => ("JKLPQRSTUXYZ", "ABCDEFGHJKLMNPQRSTUVWXYZ", 8),
+            (false, true) => ("ABCFGHJKLPQR", "ABCDEFGHJKLMNPQRSTUVWXYZ", 8),
+            (true, false) => ("RSTUXYZ", "ABCDEFGHJKLMNP", 13),
+            (true, true) => ("ABCFGHJ", "ABCDEFGHJKLMNP", 13),
         };
-        OSGB36 {
-            tm,
-            northing: 0.0,
-            easting: 0.0,
+        let column = column_letters.find(c1)? + if east { 20 } else { min_index };
+        let row = row_letters.find(c2)? + min_index;
+        Some(self.ups_to_lat_lon(
+            column as f64 * 100000.0 + easting,
+            row as f64 * 100000.0 + northing,
+            north,
+        ))
+    }
+
+    /**
+     *  Split the numerical part of a grid reference into Easting and Northing in metres
+     *  within the 100 km square, e.g. "3080" => 30000, 80000
+     */
+    fn mgrs_digits_to_offsets(digits: &[char]) -> Option<(f64, f64)> {
+        if !digits.len().is_multiple_of(2) || digits.len() > 10 {
+            return None;
         }
+        let half = digits.len() / 2;
+        let to_metres = |part: &[char]| {
+            let s: String = part.iter().collect();
+            let value = s.parse::<f64>().unwrap_or(0.0);
+            value * 10_f64.powi(5 - half as i32)
+        };
+        Some((to_metres(&digits[..half]), to_metres(&digits[half..])))
     }
-}
 
-impl OSGB36 {
-    pub const fn northing(&self) -> f64 {
-        self.northing
+    /**
+     *  Convert a lettered OSGB36 grid reference (e.g. TQ3003580414) to
+     *  Easting and Northing
+     */
+    pub fn osgb36_ref_to_easting_northing(reference: &str) -> Option<(f64, f64)> {
+        let chars: Vec<char> = reference.trim().to_ascii_uppercase().chars().collect();
+        if chars.len() < 2 || chars[2..].iter().any(|c| !c.is_ascii_digit()) {
+            return None;
+        }
+        let letters = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+        let c1_index = letters.find(chars[0])? as i32;
+        let c2_index = letters.find(chars[1])? as i32;
+
+        /* reverse of the lettering in osgb36_grid_ref */
+        let major_x = (c1_index - 17).rem_euclid(5);
+        let major_y = (17 - c1_index + major_x) / 5;
+        let minor_x = (c2_index - 20).rem_euclid(5);
+        let minor_y = (20 - c2_index + minor_x) / 5;
+        let grid_x = major_x * 5 + minor_x;
+        let grid_y = major_y * 5 + minor_y;
+
+        let (easting, northing) = Self::mgrs_digits_to_offsets(&chars[2..])?;
+        Some((
+            grid_x as f64 * 100000.0 + easting,
+            grid_y as f64 * 100000.0 + northing,
+        ))
     }
 
-    pub const fn easting(&self) -> f64 {
-        self.easting
+    /**
+     *  Convert OSGB36 Easting and Northing to
+     *  WGS-84 latitude, longitude in decimal degrees
+     */
+    pub fn osgb36_to_lat_lon(easting: f64, northing: f64) -> (f64, f64) {
+        let osgb36 = Self::new(&Ellipsoid::AIRY_1830, 0.9996012717, 400000.0, -100000.0);
+        let (latitude, longitude) = osgb36.tm_origin_to_lat_lon(easting, northing, 49.0, -2.0);
+        Helmert::WGS84_TO_OSGB36.inverse().transform_lat_lon(
+            latitude,
+            longitude,
+            &Ellipsoid::AIRY_1830,
+            &Ellipsoid::WGS84,
+        )
     }
 
-    pub fn lat_lon_to_osgb36(&mut self, latitude: f64, longitude: f64) -> String {
-        let result = self.tm.lat_lon_to_osgb36(latitude, longitude);
-        self.northing = self.tm.northing();
-        self.easting = self.tm.easting();
-        result
+    /**
+     *  Convert CH1903 (LV03) or CH1903+ (LV95) Easting and Northing to
+     *  WGS-84 latitude, longitude in decimal degrees
+     *  Approximation formula according to swisstopo, chapter 4.2
+     */
+    pub fn ch1903_to_lat_lon(easting: f64, northing: f64) -> (f64, f64) {
+        let (easting, northing) = if easting > 2000000.0 {
+            (easting - 2000000.0, northing - 1000000.0)
+        } else {
+            (easting, northing)
+        };
+        let y = (easting - 600000.0) / 1000000.0;
+        let x = (northing - 200000.0) / 1000000.0;
+
+        let lon =
+            2.6779094 + 4.728982 * y + 0.791484 * y * x + 0.1306 * y * x * x - 0.0436 * y * y * y;
+        let lat = 16.9023892 + 3.238272 * x
+            - 0.270978 * y * y
+            - 0.002528 * x * x
+            - 0.0447 * y * y * x
+            - 0.0140 * x * x * x;
+
+        (lat * 100.0 / 36.0, lon * 100.0 / 36.0)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CH1903 {
+#[derive(Debug, Clone, Default)]
+pub struct MGRS {
     tm: TransverseMercator,
-    northing: f64,
-    easting: f64,
-}
-
-impl Default for CH1903 {
-    fn default() -> Self {
-        CH1903 {
-            tm: TransverseMercator::default(),
-            northing: 0.0,
-            easting: 0.0,
-        }
-    }
+    reference: String,
 }
 
-impl CH1903 {
-    pub const fn northing(&self) -> f64 {
-        self.northing
+impl MGRS {
+    pub fn reference(&self) -> &str {
+        &self.reference
     }
 
-    pub const fn easting(&self) -> f64 {
-        self.easting
+    /// The reference with the given number of digits (0 to 5) for Easting and Northing each,
+    /// i.e. 5 for 1 m, 4 for 10 m, ..., 1 for 10 km, 0 for the 100 km square only
+    pub fn reference_with_precision(&self, digits: usize) -> String {
+        if self.reference.len() < 10 {
+            return String::new();
+        }
+        let (square, numbers) = self.reference.split_at(self.reference.len() - 10);
+        let digits = digits.min(5);
+        format!(
+            "{}{}{}",
+            square,
+            &numbers[..digits],
+            &numbers[5..5 + digits]
+        )
     }
 
-    pub fn lat_lon_to_ch1903(&mut self, latitude: f64, longitude: f64) -> bool {
-        let result = self.tm.lat_lon_to_ch1903(latitude, longitude);
-        self.northing = self.tm.northing();
-        self.easting = self.tm.easting();
-        result
+    pub fn lat_lon_to_mgrs(&mut self, latitude: f64, longitude: f64) -> String {
+        self.reference = self.tm.lat_lon_to_mgrs(latitude, longitude);
+        self.reference.clone()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::projection::Grid;
 
     #[test]
     fn test_utm_zone_calculation() {
@@ -451,7 +816,7 @@ mod tests {
     #[test]
     fn test_utm_conversion() {
         let mut tm = TransverseMercator::default();
-        tm.lat_lon_to_utm(40.7128, -74.0060);
+        assert!(tm.lat_lon_to_utm(40.7128, -74.0060).is_ok());
 
         // Check that values are set
         assert!(tm.northing() > 0.0);
@@ -460,38 +825,326 @@ mod tests {
     }
 
     #[test]
-    fn test_osgb36_conversion() {
+    fn test_tm_accuracy() {
+        // PROJ's extended transverse Mercator test points on GRS80, from test/gie/builtins.gie
+        // as ported to proj4rs (src/projections/etmerc.rs): +proj=etmerc with k0 = 1,
+        // and +proj=utm +zone=30
+        let etmerc = [
+            (1.0, 2.0, 222650.796798, 110642.229412),
+            (-1.0, 2.0, 222650.796798, -110642.229412),
+            (1.0, -2.0, -222650.796798, 110642.229412),
+            (-1.0, -2.0, -222650.796798, -110642.229412),
+        ];
+        let mut tm = TransverseMercator::new(&Ellipsoid::GRS80, 1.0, 0.0, 0.0);
+        for (lat, lon, easting, northing) in etmerc {
+            assert!(tm.lat_lon_origin_to_tm(lat, lon, 0.0, 0.0).is_ok());
+            assert!((tm.easting() - easting).abs() < 1e-4, "{lat} {lon}");
+            assert!((tm.northing() - northing).abs() < 1e-4, "{lat} {lon}");
+
+            // and back
+            let (lat2, lon2) = tm.tm_origin_to_lat_lon(easting, northing, 0.0, 0.0);
+            assert!((lat - lat2).abs() < 1e-9, "{lat} {lat2}");
+            assert!((lon - lon2).abs() < 1e-9, "{lon} {lon2}");
+        }
+
+        let utm30 = [
+            (1.0, 2.0, 1057002.405491, 110955.141176),
+            (-1.0, 2.0, 1057002.405491, -110955.141176),
+            (1.0, -2.0, 611263.812279, 110547.105697),
+            (-1.0, -2.0, 611263.812279, -110547.105697),
+        ];
+        let mut utm = TransverseMercator::new(&Ellipsoid::GRS80, 0.9996, 500000.0, 0.0);
+        for (lat, lon, easting, northing) in utm30 {
+            assert!(utm.lat_lon_origin_to_tm(lat, lon, 0.0, -3.0).is_ok());
+            assert!((utm.easting() - easting).abs() < 1e-4, "{lat} {lon}");
+            assert!((utm.northing() - northing).abs() < 1e-4, "{lat} {lon}");
+        }
+
+        // proj4rs test_utm33_grs80 (src/tests.rs), Berlin in UTM zone 33
+        assert!(
+            utm.lat_lon_origin_to_tm(52.5200080871582, 13.393921852111816, 0.0, 15.0)
+                .is_ok()
+        );
+        assert!((utm.easting() - 391027.677775).abs() < 1e-4);
+        assert!((utm.northing() - 5820089.724404).abs() < 1e-4);
+
+        // EPSG Guidance Note 7-2 example for the British National Grid, on OSGB36:
+        // 50° 30' N, 0° 30' E
+        let mut bng =
+            TransverseMercator::new(&Ellipsoid::AIRY_1830, 0.9996012717, 400000.0, -100000.0);
+        assert!(bng.lat_lon_origin_to_tm(50.5, 0.5, 49.0, -2.0).is_ok());
+        assert!((bng.easting() - 577274.99).abs() < 0.01);
+        assert!((bng.northing() - 69740.50).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_osgb36_grid_ref() {
+        let osgb36 = Grid::find("osgb36").unwrap();
+
+        // Snowdon summit trig pillar, SH 60986 54375
+        let (easting, northing) = osgb36.project(53.068497, -4.076231).unwrap();
+        assert!(TransverseMercator::osgb36_grid_ref(easting, northing).starts_with("SH6098"));
+
+        // Nelson's Column, TQ 300 804
+        let (easting2, northing2) = osgb36.project(51.507778, -0.127917).unwrap();
+        let result2 = TransverseMercator::osgb36_grid_ref(easting2, northing2);
+        assert!(result2.starts_with("TQ300"));
+        assert_eq!(&result2[7..10], "804");
+
+        // Outside the grid
+        assert!(TransverseMercator::osgb36_grid_ref(-1.0, 354375.0).is_empty());
+    }
+
+    #[test]
+    fn test_osgb36_projection() {
+        // Worked example from the OS "A guide to coordinate systems in Great Britain",
+        // with latitude and longitude already on the OSGB36 datum
+        let mut tm =
+            TransverseMercator::new(&Ellipsoid::AIRY_1830, 0.9996012717, 400000.0, -100000.0);
+        let latitude = 52.0 + 39.0 / 60.0 + 27.2531 / 3600.0;
+        let longitude = 1.0 + 43.0 / 60.0 + 4.5177 / 3600.0;
+        assert!(
+            tm.lat_lon_origin_to_tm(latitude, longitude, 49.0, -2.0)
+                .is_ok()
+        );
+        assert!((tm.easting() - 651409.903).abs() < 0.01);
+        assert!((tm.northing() - 313177.270).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_irish_grid_ref() {
+        let irishgrid = Grid::find("irishgrid").unwrap();
+        let grid_ref = |latitude, longitude| {
+            let (easting, northing) = irishgrid.project(latitude, longitude).unwrap();
+            TransverseMercator::irish_grid_ref(easting, northing)
+        };
+
+        // Spire of Dublin, O 159 346
+        assert_eq!(grid_ref(53.3498, -6.2603), "O1590034671");
+
+        // Galway, Cork, Belfast
+        assert!(grid_ref(53.2707, -9.0568).starts_with('M'));
+        assert!(grid_ref(51.8985, -8.4756).starts_with('W'));
+        assert!(grid_ref(54.5973, -5.9301).starts_with('J'));
+
+        // Outside the grid
+        assert!(TransverseMercator::irish_grid_ref(500000.0, 250000.0).is_empty());
+    }
+
+    #[test]
+    fn test_ups_conversion() {
         let mut tm = TransverseMercator::default();
 
-        // Test London coordinates
-        let result = tm.lat_lon_to_osgb36(51.5074, -0.1278);
-        assert!(!result.is_empty());
+        // Poles
+        assert!(tm.lat_lon_to_ups(90.0, 0.0).is_ok());
+        assert!((tm.easting() - 2000000.0).abs() < 0.001);
+        assert!((tm.northing() - 2000000.0).abs() < 0.001);
+        assert_eq!(tm.zone(), "Z");
+        assert!(tm.lat_lon_to_ups(-90.0, -1.0).is_ok());
+        assert_eq!(tm.zone(), "A");
+
+        assert!(tm.lat_lon_to_ups(85.0, 45.0).is_ok());
+        assert!((tm.easting() - 2392767.688).abs() < 0.01);
+        assert!((tm.northing() - 1607232.312).abs() < 0.01);
+
+        // UTM range
+        assert_eq!(
+            tm.lat_lon_to_ups(51.5074, -0.1278),
+            Err(GridError::OutsideUps(51.5074))
+        );
+        assert_eq!(
+            tm.lat_lon_to_ups(95.0, 0.0),
+            Err(GridError::InvalidCoordinates {
+                latitude: 95.0,
+                longitude: 0.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_utm_polar() {
+        // UPS north of 84° and south of -80°
+        let mut tm = TransverseMercator::default();
+        assert!(tm.lat_lon_to_utm(85.0, 45.0).is_ok());
+        assert_eq!(tm.zone(), "Z");
+        assert!((tm.easting() - 2392767.688).abs() < 0.01);
+        assert!((tm.northing() - 1607232.312).abs() < 0.01);
+        assert!(tm.lat_lon_to_utm(-85.0, -45.0).is_ok());
+        assert_eq!(tm.zone(), "A");
+
+        // Still UTM at the band edges
+        assert!(tm.lat_lon_to_utm(84.0, 45.0).is_ok());
+        assert_eq!(tm.zone(), "38X");
+        assert!(tm.lat_lon_to_utm(-80.0, 45.0).is_ok());
+        assert_eq!(tm.zone(), "38C");
+
+        assert_eq!(
+            tm.lat_lon_to_utm(40.0, 270.0),
+            Err(GridError::InvalidCoordinates {
+                latitude: 40.0,
+                longitude: 270.0
+            })
+        );
     }
 
     #[test]
-    fn test_ch1903_conversion() {
+    fn test_utm_zone_hemisphere() {
         let mut tm = TransverseMercator::default();
 
-        // Test Swiss coordinates (Bern)
-        let result1 = tm.lat_lon_to_ch1903(46.9480, 7.4474);
-        assert!(result1);
-        assert!(tm.northing() > 0.0);
-        assert!(tm.easting() > 0.0);
+        // The false northing comes from the zone letter, not the sign of the latitude
+        assert!(tm.lat_lon_zone_to_utm(-0.5, 15.0, "33N").is_ok());
+        assert!((tm.northing() + 55265.0).abs() < 1.0, "{}", tm.northing());
+        assert!(tm.lat_lon_zone_to_utm(-0.5, 15.0, "33M").is_ok());
+        assert!((tm.northing() - 9944735.0).abs() < 1.0, "{}", tm.northing());
+        assert!(tm.lat_lon_zone_to_utm(0.5, 15.0, "33M").is_ok());
+        assert!(
+            (tm.northing() - 10055265.0).abs() < 1.0,
+            "{}",
+            tm.northing()
+        );
+
+        assert_eq!(
+            tm.lat_lon_zone_to_utm(85.0, 15.0, "33X"),
+            Err(GridError::OutsideUtm(85.0))
+        );
+        for zone in ["61N", "0N", "33", "33I", "33Y", "N", "33NN"] {
+            assert_eq!(
+                tm.lat_lon_zone_to_utm(0.5, 15.0, zone),
+                Err(GridError::InvalidZone(zone.to_string()))
+            );
+        }
+        assert_eq!(
+            GridError::InvalidZone("61N".to_string()).to_string(),
+            "Invalid UTM zone \"61N\""
+        );
+        assert_eq!(
+            GridError::OutsideUtm(85.0).to_string(),
+            "Latitude 85 is outside the UTM range (-80 to 84)"
+        );
+    }
 
-        // Test out of range
-        let result2 = tm.lat_lon_to_ch1903(50.0, 0.0);
-        assert!(!result2);
+    #[test]
+    fn test_mgrs_conversion() {
+        let mut mgrs = MGRS::default();
+
+        // Washington Monument, 18S UJ 23486 06483; MGRS truncates the digits rather than rounding
+        assert_eq!(mgrs.lat_lon_to_mgrs(38.8895, -77.0352), "18SUJ2348606483");
+        assert_eq!(mgrs.reference_with_precision(4), "18SUJ23480648");
+        assert_eq!(mgrs.reference_with_precision(1), "18SUJ20");
+        assert_eq!(mgrs.reference_with_precision(0), "18SUJ");
+
+        // Sydney Opera House, southern hemisphere and even zone
+        assert_eq!(mgrs.lat_lon_to_mgrs(-33.8568, 151.2153), "56HLH3490052288");
+
+        // Poles
+        assert_eq!(mgrs.lat_lon_to_mgrs(90.0, 0.0), "ZAH0000000000");
+        assert_eq!(mgrs.lat_lon_to_mgrs(-90.0, 0.0), "BAN0000000000");
+
+        // Out of range
+        assert_eq!(mgrs.lat_lon_to_mgrs(40.0, 270.0), "");
+        assert_eq!(mgrs.reference_with_precision(3), "");
     }
 
     #[test]
-    fn test_deg_rad_conversion() {
-        fn rad2deg(rad: f64) -> f64 {
-            rad * (180.0 / PI)
+    fn test_utm_inverse() {
+        let mut tm = TransverseMercator::default();
+        for (lat, lon) in [(49.48, 8.47), (40.7128, -74.006), (-33.8568, 151.2153)] {
+            assert!(tm.lat_lon_to_utm(lat, lon).is_ok());
+            let (lat2, lon2) = tm
+                .utm_to_lat_lon(tm.easting(), tm.northing(), tm.zone())
+                .unwrap();
+            assert!((lat - lat2).abs() < 1e-8, "{lat} {lat2}");
+            assert!((lon - lon2).abs() < 1e-8, "{lon} {lon2}");
         }
+        assert!(tm.utm_to_lat_lon(461344.0, 5481745.0, "61U").is_none());
+        assert!(tm.utm_to_lat_lon(461344.0, 5481745.0, "32").is_none());
+    }
+
+    #[test]
+    fn test_mgrs_inverse() {
+        let tm = TransverseMercator::default();
+
+        let (lat, lon) = tm.mgrs_to_lat_lon("18SUJ2348606483").unwrap();
+        assert!((lat - 38.8895).abs() < 1e-4);
+        assert!((lon + 77.0352).abs() < 1e-4);
+
+        let (lat2, lon2) = tm.mgrs_to_lat_lon("56HLH3490052288").unwrap();
+        assert!((lat2 + 33.8568).abs() < 1e-4);
+        assert!((lon2 - 151.2153).abs() < 1e-4);
+
+        // 1 km precision, south-west corner of the square
+        let (lat3, lon3) = tm.mgrs_to_lat_lon("18suj2306").unwrap();
+        assert!((lat3 - 38.886).abs() < 1e-2);
+        assert!((lon3 + 77.04).abs() < 1e-2);
+
+        let (lat4, _lon4) = tm.mgrs_to_lat_lon("ZAH0000000000").unwrap();
+        assert!((lat4 - 90.0).abs() < 1e-8);
+        let (lat5, lon5) = tm.mgrs_to_lat_lon("BAN0000000000").unwrap();
+        assert!((lat5 + 90.0).abs() < 1e-8);
+        assert!(lon5.abs() < 1e-8);
+
+        let mut mgrs = MGRS::default();
+        mgrs.lat_lon_to_mgrs(85.0, 45.0);
+        let (lat6, lon6) = tm.mgrs_to_lat_lon(mgrs.reference()).unwrap();
+        assert!((lat6 - 85.0).abs() < 1e-4);
+        assert!((lon6 - 45.0).abs() < 1e-4);
+
+        assert!(tm.mgrs_to_lat_lon("18SUJ234860648").is_none());
+        assert!(tm.mgrs_to_lat_lon("18IUJ2348606483").is_none());
+        assert!(tm.mgrs_to_lat_lon("nonsense").is_none());
+    }
+
+    #[test]
+    fn test_osgb36_inverse() {
+        let (easting, northing) =
+            TransverseMercator::osgb36_ref_to_easting_northing("SH6098654375").unwrap();
+        assert_eq!((easting, northing), (260986.0, 354375.0));
+        assert_eq!(
+            TransverseMercator::osgb36_ref_to_easting_northing("TQ3080"),
+            Some((530000.0, 180000.0))
+        );
+        assert_eq!(
+            TransverseMercator::osgb36_ref_to_easting_northing("NN1667071288"),
+            Some((216670.0, 771288.0))
+        );
+        assert!(TransverseMercator::osgb36_ref_to_easting_northing("TQ308").is_none());
+        assert!(TransverseMercator::osgb36_ref_to_easting_northing("T").is_none());
+
+        // Snowdon, back to WGS-84
+        let (lat, lon) = TransverseMercator::osgb36_to_lat_lon(easting, northing);
+        assert!((lat - 53.068497).abs() < 5e-5);
+        assert!((lon + 4.076231).abs() < 5e-5);
+
+        // Round trip
+        let (easting1, northing1) = Grid::find("osgb36")
+            .unwrap()
+            .project(51.5074, -0.1278)
+            .unwrap();
+        let reference = TransverseMercator::osgb36_grid_ref(easting1, northing1);
+        let (easting2, northing2) =
+            TransverseMercator::osgb36_ref_to_easting_northing(&reference).unwrap();
+        let (lat2, lon2) = TransverseMercator::osgb36_to_lat_lon(easting2, northing2);
+        assert!((lat2 - 51.5074).abs() < 2e-5);
+        assert!((lon2 + 0.1278).abs() < 2e-5);
+    }
 
+    #[test]
+    fn test_ch1903_inverse() {
+        // swisstopo reference point
+        let (lat, lon) = TransverseMercator::ch1903_to_lat_lon(2700000.0, 1100000.0);
+        assert!((lat - (46.0 + 2.0 / 60.0 + 38.87 / 3600.0)).abs() < 2e-5);
+        assert!((lon - (8.0 + 43.0 / 60.0 + 49.79 / 3600.0)).abs() < 2e-5);
+
+        let (lat2, lon2) = TransverseMercator::ch1903_to_lat_lon(700000.0, 100000.0);
+        assert!((lat - lat2).abs() < 1e-10);
+        assert!((lon - lon2).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_deg_rad_conversion() {
         let deg = 45.0;
         let rad = TransverseMercator::deg2rad(deg);
-        let back = rad2deg(rad);
+        let back = TransverseMercator::rad2deg(rad);
 
         assert!((deg - back).abs() < 1e-10);
     }
@@ -0,0 +1,122 @@
+use crate::geo_attributes::COMPASS_POINTS;
+use crate::geo_param::GeoParam;
+use std::collections::HashMap;
+
+/// Mean radius of the Earth, in metres
+const EARTH_RADIUS_M: f64 = 6_371_008.8;
+/// Distance of the target point without `dim:`, about what a street-level camera shows
+const DEFAULT_TARGET_DISTANCE_M: f64 = 100.0;
+
+/// The direction a camera looks in, from `heading:`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ViewingDirection {
+    heading: Option<f64>,
+    /// The point the camera looks at, `dim:` metres (or 100 m) away. Earth only.
+    target: Option<(f64, f64)>,
+}
+
+impl ViewingDirection {
+    pub fn new(p: &GeoParam) -> Self {
+        let attr = p.attributes();
+        let heading = attr.heading();
+        let target = heading.filter(|_| p.is_earth()).map(|heading| {
+            let distance = attr.dim().unwrap_or(DEFAULT_TARGET_DISTANCE_M);
+            Self::destination(p.latdeg(), p.londeg(), heading, distance)
+        });
+        Self { heading, target }
+    }
+
+    /// The nearest of the 16 compass points, e.g. "NE" for 50°
+    fn compass(heading: f64) -> &'static str {
+        let index = (heading / 22.5).round() as usize % COMPASS_POINTS.len();
+        COMPASS_POINTS[index]
+    }
+
+    /// The point `distance` metres from (lat, lon) in direction `heading`, on a sphere
+    fn destination(lat: f64, lon: f64, heading: f64, distance: f64) -> (f64, f64) {
+        let (lat1, lon1) = (lat.to_radians(), lon.to_radians());
+        let bearing = heading.to_radians();
+        let delta = distance / EARTH_RADIUS_M;
+        let lat2 = (lat1.sin() * delta.cos() + lat1.cos() * delta.sin() * bearing.cos()).asin();
+        let lon2 = lon1
+            + (bearing.sin() * delta.sin() * lat1.cos())
+                .atan2(delta.cos() - lat1.sin() * lat2.sin());
+        /* longitude in [-180,180) */
+        let lon2 = (lon2.to_degrees() + 180.0).rem_euclid(360.0) - 180.0;
+        (lat2.to_degrees(), lon2)
+    }
+
+    pub fn add_rep_map(&self, rep_map: &mut HashMap<String, String>) {
+        let (targetlat, targetlon) = self
+            .target
+            .map(|(lat, lon)| (format!("{lat:.6}"), format!("{lon:.6}")))
+            .unwrap_or_default();
+        insert_map!(rep_map, {
+            "heading" => self.heading.map(|h| h.to_string()).unwrap_or_default(),
+            "heading_compass" => self.heading.map(Self::compass).unwrap_or_default(),
+            "targetlat" => targetlat,
+            "targetlon" => targetlon,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compass() {
+        assert_eq!(ViewingDirection::compass(0.0), "N");
+        assert_eq!(ViewingDirection::compass(50.0), "NE");
+        assert_eq!(ViewingDirection::compass(247.5), "WSW");
+        assert_eq!(ViewingDirection::compass(355.0), "N");
+    }
+
+    #[test]
+    fn test_destination() {
+        // One degree of latitude to the north
+        let (lat, lon) = ViewingDirection::destination(10.0, 20.0, 0.0, 111_195.08);
+        assert!((lat - 11.0).abs() < 1e-6);
+        assert!((lon - 20.0).abs() < 1e-9);
+
+        // Due east across the antimeridian, at the equator
+        let (east_lat, east_lon) = ViewingDirection::destination(0.0, 179.5, 90.0, 111_195.08);
+        assert!(east_lat.abs() < 1e-9);
+        assert!((east_lon + 179.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_viewing_direction_rep_map() {
+        let geo = GeoParam::new("48.8584_N_2.2945_E_type:camera_heading:NE").unwrap();
+        let mut rep_map = HashMap::new();
+        ViewingDirection::new(&geo).add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("heading").unwrap(), "45");
+        assert_eq!(rep_map.get("heading_compass").unwrap(), "NE");
+        // 100 m to the north-east
+        assert_eq!(rep_map.get("targetlat").unwrap(), "48.859036");
+        assert_eq!(rep_map.get("targetlon").unwrap(), "2.295467");
+
+        let dim = GeoParam::new("48.8584_N_2.2945_E_heading:180_dim:1km").unwrap();
+        let mut dim_map = HashMap::new();
+        ViewingDirection::new(&dim).add_rep_map(&mut dim_map);
+        assert_eq!(dim_map.get("heading_compass").unwrap(), "S");
+        assert_eq!(dim_map.get("targetlat").unwrap(), "48.849407");
+    }
+
+    #[test]
+    fn test_viewing_direction_without_heading() {
+        let geo = GeoParam::new("48.8584_N_2.2945_E_type:camera").unwrap();
+        let mut rep_map = HashMap::new();
+        ViewingDirection::new(&geo).add_rep_map(&mut rep_map);
+        assert_eq!(rep_map.get("heading").unwrap(), "");
+        assert_eq!(rep_map.get("heading_compass").unwrap(), "");
+        assert_eq!(rep_map.get("targetlat").unwrap(), "");
+
+        // No target on other globes
+        let mars = GeoParam::new("9.3_S_322_W_globe:Mars_heading:90").unwrap();
+        let mut mars_map = HashMap::new();
+        ViewingDirection::new(&mars).add_rep_map(&mut mars_map);
+        assert_eq!(mars_map.get("heading").unwrap(), "90");
+        assert_eq!(mars_map.get("targetlon").unwrap(), "");
+    }
+}